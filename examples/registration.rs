@@ -5,6 +5,7 @@ fn main() {
     // Create bridge by registering with the Philips Hue Bridge
     let (b, token) = bridge::Bridge::try_register(true).unwrap();
 
-    // Print out the whole bridge
+    // Print out the whole bridge and the token to keep for later
     println!("Created bridge: {:#?}", b);
+    println!("Token: {}", token);
 }
@@ -0,0 +1,138 @@
+/// # Adapt module
+///
+/// This module (gated under the `color` feature) translates a requested
+/// `SendableState` into what a given light is actually able to do, so that one
+/// command can be sent to a mixed set of lights:
+///
+/// - color lights get their `xy` clamped into their gamut, and a color temperature
+///   turned into the matching `xy` when they do not support `ct`
+/// - color temperature lights get colors turned into the nearest `ct`
+/// - dimmable lights only keep the brightness
+/// - plugs only keep `on` and `alert`
+///
+/// ```
+/// use lighthouse::{adapt::adapt_state, lights::*, state};
+/// let light: Light = serde_json::from_str(r#"{
+///     "name": "Hallway", "type": "Color temperature light",
+///     "modelid": "LTW012", "manufacturername": "Signify Netherlands B.V.",
+///     "state": {"on": true, "bri": 254, "ct": 366, "reachable": true}
+/// }"#).unwrap();
+/// let adapted = adapt_state(state!(on: true, xy: [0.5612, 0.4042]), &light);
+/// assert_eq!(adapted.xy, None);
+/// assert_eq!(adapted.ct, Some(500));
+/// ```
+// imports
+use crate::{
+    color::{self, mired_to_xy},
+    lights::{Capabilities, Gamut, Light, LightType, SendableState},
+};
+
+/// Narrowest color temperature range accepted by Hue lights, in mireds
+const DEFAULT_CT_RANGE: (u16, u16) = (153, 500);
+
+/// What a light can do, worked out from its type, capabilities and state
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Features {
+    color: bool,
+    ct: Option<(u16, u16)>,
+    dimming: bool,
+    gamut: Option<Gamut>,
+}
+
+impl Features {
+    fn of(light: &Light) -> Self {
+        let capabilities: &Capabilities = &light.capabilities;
+        let color = capabilities.supports_color()
+            || matches!(light.kind, LightType::Color | LightType::ExtendedColor)
+            || light.state.xy.is_some();
+        let ct = capabilities
+            .control
+            .ct
+            .map(|range| (range.min, range.max))
+            .or_else(|| {
+                let reports_ct = matches!(
+                    light.kind,
+                    LightType::ColorTemperature | LightType::ExtendedColor
+                ) || light.state.ct.is_some();
+                if reports_ct {
+                    Some(DEFAULT_CT_RANGE)
+                } else {
+                    None
+                }
+            });
+        let dimming = match light.kind {
+            LightType::OnOffPlug => false,
+            LightType::Other(_) => light.state.bri.is_some(),
+            _ => true,
+        };
+        Self {
+            color,
+            ct,
+            dimming,
+            gamut: capabilities.gamut(),
+        }
+    }
+}
+
+/// Translates `state` into what `light` is able to do.
+///
+/// Fields the light does not support are dropped or converted, the others are
+/// kept as they are.
+pub fn adapt_state(state: &SendableState, light: &Light) -> SendableState {
+    let features = Features::of(light);
+    let mut adapted = state.clone();
+
+    if !features.dimming {
+        return SendableState {
+            on: state.on,
+            alert: state.alert,
+            transitiontime: None,
+            ..SendableState::default()
+        };
+    }
+
+    if features.color {
+        if features.ct.is_none() {
+            if let Some(ct) = adapted.ct.take() {
+                if adapted.xy.is_none() && adapted.hue.is_none() {
+                    adapted.xy = Some(mired_to_xy(ct));
+                }
+            }
+            adapted.ct_inc = None;
+        }
+        if let (Some(xy), Some(gamut)) = (adapted.xy, features.gamut) {
+            adapted.xy = Some(gamut.closest(xy));
+        }
+    } else {
+        let color = adapted.xy.or_else(|| hs_to_xy(adapted.hue, adapted.sat));
+        if let Some((min, max)) = features.ct {
+            if adapted.ct.is_none() {
+                adapted.ct = color.map(|xy| closest_mired(xy, min, max));
+            }
+            adapted.ct = adapted.ct.map(|ct| ct.clamp(min, max.max(min)));
+        } else {
+            adapted.ct = None;
+            adapted.ct_inc = None;
+        }
+        adapted.xy = None;
+        adapted.hue = None;
+        adapted.sat = None;
+        adapted.xy_inc = None;
+        adapted.hue_inc = None;
+        adapted.sat_inc = None;
+        adapted.effect = None;
+    }
+    adapted.colormode = None;
+    adapted
+}
+
+/// Color temperature within `min..=max` mireds closest to a color
+fn closest_mired(xy: [f32; 2], min: u16, max: u16) -> u16 {
+    color::kelvin_to_mired(color::xy_to_kelvin(xy)).clamp(min, max.max(min))
+}
+
+/// Color of a hue and saturation at full brightness, `None` without a hue
+fn hs_to_xy(hue: Option<u16>, sat: Option<u8>) -> Option<[f32; 2]> {
+    let rgb = color::hs_to_rgb(hue?, sat.unwrap_or(254), 254);
+    Some(color::rgb_to_xy(rgb))
+}
@@ -3,86 +3,303 @@
 /// This module contains the Bridge and related functionality
 // imports
 use super::{
+    config::*,
+    error::{Error, Result},
+    groups::*,
     helpers::{network::*, *},
     lights::*,
+    response::{ApiError, ApiResponse, ErrorType},
+    rules::*,
+    scenes::*,
+    schedules::*,
+    search::*,
+    sensors::*,
 };
-use std::cell::RefCell;
+use serde::{de::DeserializeOwned, Serialize};
 use std::collections::BTreeMap;
 use std::net::IpAddr;
-use tokio::runtime::Runtime;
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::time::Duration;
+use tokio::runtime::{Handle, Runtime};
 use url::Url;
 
-/// # Take it to the Bridge!
+/// # Bridge builder
 ///
-/// This is the Bridge object - the core of the library.
+/// Collects everything required to talk to a bridge and builds either a blocking
+/// [Bridge](struct.Bridge.html) or an [AsyncBridge](struct.AsyncBridge.html).
 ///
-/// This is the manager struct that implements all the core methods that are required to interact
-/// with the Hue Bridge. It has a bunch of convenience functions such as sending state, scanning for lights,
-/// getting the information about your existing lights and finding bridge IP addresses with SSDP.
+/// ```no_run
+/// use std::time::Duration;
+/// use lighthouse::bridge::BridgeBuilder;
+/// let bridge = BridgeBuilder::new()
+///     .host("philips-hue.local")
+///     .token("my-example-token")
+///     .timeout(Duration::from_secs(2))
+///     .validate()
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct BridgeBuilder {
+    host: Option<String>,
+    port: Option<u16>,
+    scheme: String,
+    token: Option<String>,
+    timeout: Option<Duration>,
+    handle: Option<Handle>,
+    client: Option<reqwest::Client>,
+    validate: bool,
+}
+
+impl Default for BridgeBuilder {
+    fn default() -> Self {
+        Self {
+            host: None,
+            port: None,
+            scheme: String::from("http"),
+            token: None,
+            timeout: None,
+            handle: None,
+            client: None,
+            validate: false,
+        }
+    }
+}
+
+impl BridgeBuilder {
+    /// Creates a builder with the default `http` scheme and port
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the IP address of the bridge
+    pub fn ip(mut self, ip: IpAddr) -> Self {
+        self.host = Some(match ip {
+            IpAddr::V4(ip) => ip.to_string(),
+            IpAddr::V6(ip) => format!("[{}]", ip),
+        });
+        self
+    }
+
+    /// Sets the hostname (or IP address) of the bridge
+    pub fn host(mut self, host: impl Into<String>) -> Self {
+        self.host = Some(host.into());
+        self
+    }
+
+    /// Sets a non-standard port, useful to talk to a mock bridge
+    pub fn port(mut self, port: u16) -> Self {
+        self.port = Some(port);
+        self
+    }
+
+    /// Sets the scheme, `http` by default
+    pub fn scheme(mut self, scheme: impl Into<String>) -> Self {
+        self.scheme = scheme.into();
+        self
+    }
+
+    /// Sets the whitelisted token (username) used to authenticate with the bridge
+    pub fn token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// Sets a timeout for every request sent to the bridge.
+    ///
+    /// This is ignored if a pre-configured client is provided with `client`.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Uses a runtime owned by the caller for the blocking `Bridge` instead of
+    /// creating a new one.
+    ///
+    /// **NOTE:** the runtime needs to be driven while the bridge blocks on it, so
+    /// this should be the handle of a threaded runtime.
+    pub fn runtime_handle(mut self, handle: Handle) -> Self {
+        self.handle = Some(handle);
+        self
+    }
+
+    /// Uses a pre-configured `reqwest::Client` for all requests
+    pub fn client(mut self, client: reqwest::Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Checks that the token is whitelisted on the bridge before returning it
+    pub fn validate(mut self) -> Self {
+        self.validate = true;
+        self
+    }
+
+    /// Builds the blocking `Bridge`, validating the token if requested
+    pub fn build(self) -> Result<Bridge> {
+        let runtime = match &self.handle {
+            Some(handle) => BridgeRuntime::External(handle.clone()),
+            None => BridgeRuntime::Owned(create_runtime()?),
+        };
+        let validate = self.validate;
+        let bridge = Bridge::with_runtime(self.assemble()?, runtime);
+        if validate {
+            bridge.block_on(bridge.inner.validate())?;
+        }
+        Ok(bridge)
+    }
+
+    /// Builds the `AsyncBridge`, validating the token if requested
+    pub async fn build_async(self) -> Result<AsyncBridge> {
+        let validate = self.validate;
+        let bridge = self.assemble()?;
+        if validate {
+            bridge.validate().await?;
+        }
+        Ok(bridge)
+    }
+
+    /// Puts together the `AsyncBridge` without touching the network
+    fn assemble(self) -> Result<AsyncBridge> {
+        let host = self
+            .host
+            .ok_or_else(|| Error::InvalidTarget(String::from("no host was provided")))?;
+        let token = self
+            .token
+            .ok_or_else(|| Error::InvalidTarget(String::from("no token was provided")))?;
+        let target = generate_url(&self.scheme, &host, self.port, &token)?;
+        let client = match self.client {
+            Some(client) => client,
+            None => {
+                let mut builder = reqwest::Client::builder();
+                if let Some(timeout) = self.timeout {
+                    builder = builder.timeout(timeout);
+                }
+                builder.build()?
+            }
+        };
+        Ok(AsyncBridge {
+            target,
+            token,
+            client,
+            lights: RwLock::new(None),
+            light_ids: RwLock::new(None),
+        })
+    }
+}
+
+/// # The asynchronous Bridge
 ///
-/// Additional features can be enabled:
-/// - `persist` - enables building a bridge from environment variables and serialising to file
+/// This is the async counterpart of the [Bridge](struct.Bridge.html) and the place where
+/// all the requests to the Hue Bridge are actually built and sent. It does not own a runtime,
+/// so it can be used from within an existing tokio application.
+///
+/// The bridge is `Send + Sync`, so it can be put in an `Arc` and shared between tasks. The
+/// light cache sits behind a lock that is never held across an `.await`.
+///
+/// ```no_run
+/// # async fn run() -> Result<(), lighthouse::Error> {
+/// use lighthouse::{bridge::AsyncBridge, state};
+/// let bridge = AsyncBridge::new("192.168.1.10".parse().unwrap(), "token".to_string())?;
+/// let lights = bridge.get_lights().await?;
+/// bridge.state_to(1, state!(on: true, bri: 128)).await?;
+/// # Ok(())
+/// # }
+/// ```
 #[derive(Debug)]
-pub struct Bridge {
+pub struct AsyncBridge {
     pub target: Url,
-    ip: IpAddr,
     token: String,
     client: reqwest::Client,
-    // TODO: The use of refcells here does not make it multithread safe. Might be worth adding that later with a feature flag?
-    runtime: RefCell<Runtime>,
-    // TODO: Unclear if this is the best way to structure this. Should refcell be inside?
-    lights: RefCell<Option<BTreeMap<u8, Light>>>,
-    light_ids: RefCell<Option<Vec<u8>>>,
+    // NOTE: always take the `lights` lock before the `light_ids` one
+    lights: RwLock<Option<BTreeMap<u8, Light>>>,
+    light_ids: RwLock<Option<Vec<u8>>>,
 }
 
-impl Bridge {
+impl AsyncBridge {
     /// Constructor for a bridge from and IP and a token
-    pub fn new(ip: IpAddr, token: String) -> Result<Self, ()> {
-        let target = generate_target(ip, &token)?;
+    ///
+    /// Use the [BridgeBuilder](struct.BridgeBuilder.html) for more control.
+    pub fn new(ip: IpAddr, token: String) -> Result<Self> {
+        BridgeBuilder::new().ip(ip).token(token).assemble()
+    }
+
+    /// Checks that the token of the bridge is whitelisted.
+    ///
+    /// The bridge answers `/api/<token>/config` even for unknown tokens, but only
+    /// includes the `whitelist` when the token is authorized.
+    pub async fn validate(&self) -> Result<()> {
+        let config: serde_json::Value = self.get_resource("./config").await?;
+        if config.get("whitelist").is_none() {
+            return Err(Error::Api(ApiError {
+                kind: ErrorType::UnauthorizedUser,
+                address: "/config".into(),
+                description: "unauthorized user".into(),
+            }));
+        }
+        Ok(())
+    }
+
+    /// Fetches the part of the configuration the bridge shares without a token,
+    /// enough to identify it before registering.
+    pub async fn public_config(ip: IpAddr) -> Result<PublicConfig> {
+        let mut target = generate_target(ip, "")?;
+        target.set_path("api/config");
         let client = reqwest::Client::new();
-        let runtime = RefCell::new(
-            tokio::runtime::Builder::new()
-                .basic_scheduler()
-                .enable_all()
-                .build()
-                .expect("Could not create tokio runtime during the creation of the Bridge"),
-        );
-        let lights = RefCell::new(None);
-        let light_ids = RefCell::new(None);
-        Ok(Bridge {
-            target,
-            ip,
-            token,
-            client,
-            runtime,
-            lights,
-            light_ids,
-        })
+        parse_response(send_request((target, AllowedMethod::GET), None, &client).await?).await
     }
 
     /// Scan the existing lights on the network. Returns the light id
     /// mapped to the light object.
-    fn scan(&self) -> BTreeMap<u8, Light> {
-        let endpoint = self.get_endpoint("./lights", AllowedMethod::GET);
-        let fut = send_request(endpoint, None, &self.client);
-        let lights: BTreeMap<u8, Light> = self
-            .runtime
-            .borrow_mut()
-            .block_on(async { fut.await?.json().await })
-            .expect("Could not completely decode/send request");
-        lights
+    async fn scan(&self) -> Result<BTreeMap<u8, Light>> {
+        self.get_resource("./lights").await
     }
 
     /// Updates the lights in the system by scanning them if
     /// the user either forces it through the bool parameter or
-    /// the inner option of the RefCell is None.
-    fn update_lights(&self, force: bool) {
-        if self.lights.borrow().is_none() || force {
-            let lights = self.scan();
-            let ids = lights.keys().cloned().collect();
-            self.lights.replace(Some(lights));
-            self.light_ids.replace(Some(ids));
+    /// the cache is empty.
+    ///
+    /// Concurrent callers may both end up scanning, the last scan to finish wins.
+    pub async fn update_lights(&self, force: bool) -> Result<()> {
+        if read_lock(&self.lights).is_none() || force {
+            let lights = self.scan().await?;
+            self.store_lights(lights);
         }
+        Ok(())
+    }
+
+    /// A copy of the cached lights, scanned first if the cache is empty.
+    ///
+    /// The lights are returned rather than read back from the cache, so another task
+    /// invalidating the cache in the meantime cannot leave the caller without lights.
+    async fn cached_lights(&self) -> Result<BTreeMap<u8, Light>> {
+        let cached = read_lock(&self.lights).clone();
+        match cached {
+            Some(lights) => Ok(lights),
+            None => {
+                let lights = self.scan().await?;
+                self.store_lights(lights.clone());
+                Ok(lights)
+            }
+        }
+    }
+
+    /// The ids of the cached lights, scanned first if the cache is empty
+    async fn cached_light_ids(&self) -> Result<Vec<u8>> {
+        let cached = read_lock(&self.light_ids).clone();
+        match cached {
+            Some(ids) => Ok(ids),
+            None => Ok(self.cached_lights().await?.keys().cloned().collect()),
+        }
+    }
+
+    /// Replaces the cached lights and their ids
+    fn store_lights(&self, lights: BTreeMap<u8, Light>) {
+        let ids = lights.keys().cloned().collect();
+        let mut cached_lights = write_lock(&self.lights);
+        let mut cached_ids = write_lock(&self.light_ids);
+        *cached_lights = Some(lights);
+        *cached_ids = Some(ids);
     }
 
     /// Get the lights from the bridge struct. It performs a rescan and
@@ -90,82 +307,1008 @@ impl Bridge {
     /// done yet.
     ///
     /// This performs a clone rather than returning a reference.
-    pub fn get_lights(&self) -> BTreeMap<u8, Light> {
-        self.update_lights(false);
-        self.lights
-            .borrow()
-            .clone()
-            .expect("This should always be some since we update before accessing")
+    pub async fn get_lights(&self) -> Result<BTreeMap<u8, Light>> {
+        self.cached_lights().await
     }
 
     /// Sends a state to a given light by its ID on the system.
     ///
     /// This is useful when you want to send a given state to one light
     /// on the network.
-    pub fn state_to(&self, id: u8, new_state: &SendableState) -> reqwest::Response {
-        let endpoint = self.get_endpoint(&format!("./lights/{}/state", id)[..], AllowedMethod::PUT);
-        self.runtime
-            .borrow_mut()
-            .block_on(send_request(endpoint, Some(new_state), &self.client))
-            .expect(&format!("Could not send state to light: {}", id)[..])
+    ///
+    /// The returned `ApiResponse` holds the per-attribute outcome, so a light that
+    /// is switched off shows up as an `ErrorType::DeviceOff` entry rather than an `Err`.
+    pub async fn state_to(&self, id: u8, new_state: &SendableState) -> Result<ApiResponse> {
+        let endpoint =
+            self.get_endpoint(&format!("./lights/{}/state", id)[..], AllowedMethod::PUT)?;
+        parse_api_response(send_request(endpoint, Some(new_state), &self.client).await?).await
     }
 
     /// Sends a state to all lights in the system
     ///
     /// The method sends a given state change to all possible lights on the system.
-    /// It also performs an update using `Self::update_lights` in order to make sure all
-    /// lights have been retrieved at least once.
-    pub fn to_all(
-        &self,
-        new_state: &SendableState,
-    ) -> Result<Vec<reqwest::Response>, reqwest::Error> {
-        self.update_lights(false);
-        let endpoints: Vec<_> = self
-            .light_ids // get lights
-            .borrow()
-            .as_ref()
-            .map(|ids| {
-                ids.iter().map(|id| {
-                    self.get_endpoint(&format!("./lights/{}/state", id)[..], AllowedMethod::PUT)
-                })
-            })
-            .expect("No values in lights")
-            .collect();
-        self.runtime
-            .borrow_mut()
-            .block_on(send_requests(
-                endpoints,
-                std::iter::repeat(Some(new_state)),
-                &self.client,
-            ))
-            .into_iter()
-            .collect()
+    /// The lights are scanned first if they have not been retrieved yet.
+    pub async fn to_all(&self, new_state: &SendableState) -> Result<Vec<ApiResponse>> {
+        let endpoints = self
+            .cached_light_ids()
+            .await?
+            .iter()
+            .map(|id| self.get_endpoint(&format!("./lights/{}/state", id)[..], AllowedMethod::PUT))
+            .collect::<Result<Vec<_>>>()?;
+        let responses =
+            send_requests(endpoints, std::iter::repeat(Some(new_state)), &self.client).await;
+        parse_api_responses(responses).await
     }
 
     /// Send a state object to all lights on the network.
-    pub fn state_to_multiple<'a>(
+    pub async fn state_to_multiple<'a>(
         &self,
         ids: impl IntoIterator<Item = u8>,
         new_states: impl IntoIterator<Item = &'a SendableState>,
-    ) -> Result<Vec<reqwest::Response>, reqwest::Error> {
-        let endpoints: Vec<_> = ids
+    ) -> Result<Vec<ApiResponse>> {
+        let endpoints = ids
             .into_iter()
             .map(|id| self.get_endpoint(&format!("./lights/{}/state", id)[..], AllowedMethod::PUT))
-            .collect();
+            .collect::<Result<Vec<_>>>()?;
         let states = new_states.into_iter().map(Some);
 
-        self.runtime
-            .borrow_mut()
-            .block_on(send_requests(endpoints, states, &self.client))
+        parse_api_responses(send_requests(endpoints, states, &self.client).await).await
+    }
+
+    /// Sends a state to all lights in the system, adapted to what each light can do.
+    ///
+    /// See the [adapt](../adapt/index.html) module for how states are adapted, lights
+    /// are looked up in the cached lights.
+    #[cfg(feature = "color")]
+    pub async fn to_all_adapted(&self, new_state: &SendableState) -> Result<Vec<ApiResponse>> {
+        let (ids, states): (Vec<u8>, Vec<SendableState>) = self
+            .cached_lights()
+            .await?
+            .iter()
+            .map(|(id, light)| (*id, crate::adapt::adapt_state(new_state, light)))
+            .unzip();
+        self.state_to_multiple(ids, &states).await
+    }
+
+    /// Sends states to several lights, each adapted to what its light can do.
+    ///
+    /// Lights missing from the cached lights are sent their state unchanged.
+    #[cfg(feature = "color")]
+    pub async fn state_to_multiple_adapted<'a>(
+        &self,
+        ids: impl IntoIterator<Item = u8>,
+        new_states: impl IntoIterator<Item = &'a SendableState>,
+    ) -> Result<Vec<ApiResponse>> {
+        let lights = self.cached_lights().await?;
+        let (ids, states): (Vec<u8>, Vec<SendableState>) = ids
             .into_iter()
-            .collect()
+            .zip(new_states)
+            .map(|(id, state)| {
+                let adapted = match lights.get(&id) {
+                    Some(light) => crate::adapt::adapt_state(state, light),
+                    None => state.clone(),
+                };
+                (id, adapted)
+            })
+            .unzip();
+        self.state_to_multiple(ids, &states).await
+    }
+
+    /// Renames a light, updating the cached lights
+    pub async fn rename_light(&self, id: u8, name: &str) -> Result<ApiResponse> {
+        let body = serde_json::json!({ "name": name });
+        let response = self
+            .send_to_resource(
+                &format!("./lights/{}", id)[..],
+                AllowedMethod::PUT,
+                Some(&body),
+            )
+            .await?
+            .into_result()?;
+        if let Some(light) = write_lock(&self.lights)
+            .as_mut()
+            .and_then(|lights| lights.get_mut(&id))
+        {
+            light.name = name.to_owned();
+        }
+        Ok(response)
+    }
+
+    /// Deletes a light from the bridge, removing it from the cached lights
+    pub async fn delete_light(&self, id: u8) -> Result<ApiResponse> {
+        let response = self
+            .delete_resource(&format!("./lights/{}", id)[..])
+            .await?;
+        let mut cached_lights = write_lock(&self.lights);
+        let mut cached_ids = write_lock(&self.light_ids);
+        if let Some(lights) = cached_lights.as_mut() {
+            lights.remove(&id);
+        }
+        if let Some(ids) = cached_ids.as_mut() {
+            ids.retain(|cached| *cached != id);
+        }
+        Ok(response)
+    }
+
+    /// Updates the configuration of a light, only the fields set in `config` are sent.
+    ///
+    /// Errors for individual attributes are kept in the `ApiResponse`. The cached
+    /// lights are invalidated so the new configuration is fetched when next needed.
+    pub async fn update_light_config(
+        &self,
+        id: u8,
+        config: &LightConfigUpdate,
+    ) -> Result<ApiResponse> {
+        let response = self
+            .send_to_resource(
+                &format!("./lights/{}/config", id)[..],
+                AllowedMethod::PUT,
+                Some(config),
+            )
+            .await;
+        self.invalidate_lights();
+        response
+    }
+
+    /// Get all the groups defined on the bridge, mapped by their id.
+    ///
+    /// This does not include the special group 0 containing all lights, use
+    /// `get_group(0)` for that one.
+    pub async fn get_groups(&self) -> Result<BTreeMap<u8, Group>> {
+        self.get_resource("./groups").await
+    }
+
+    /// Get a single group by its id
+    pub async fn get_group(&self, id: u8) -> Result<Group> {
+        self.get_resource(&format!("./groups/{}", id)[..]).await
+    }
+
+    /// Creates a new group and returns its id
+    pub async fn create_group(&self, group: &NewGroup) -> Result<u8> {
+        self.create_numeric_resource("./groups", group, "group")
+            .await
+    }
+
+    /// Renames a group
+    pub async fn rename_group(&self, id: u8, name: &str) -> Result<ApiResponse> {
+        let body = serde_json::json!({ "name": name });
+        self.send_to_resource(
+            &format!("./groups/{}", id)[..],
+            AllowedMethod::PUT,
+            Some(&body),
+        )
+        .await?
+        .into_result()
+    }
+
+    /// Replaces the lights that are members of a group
+    pub async fn set_group_lights(&self, id: u8, lights: &[u8]) -> Result<ApiResponse> {
+        let lights: Vec<String> = lights.iter().map(|id| id.to_string()).collect();
+        let body = serde_json::json!({ "lights": lights });
+        self.send_to_resource(
+            &format!("./groups/{}", id)[..],
+            AllowedMethod::PUT,
+            Some(&body),
+        )
+        .await?
+        .into_result()
+    }
+
+    /// Deletes a group from the bridge
+    pub async fn delete_group(&self, id: u8) -> Result<ApiResponse> {
+        self.delete_resource(&format!("./groups/{}", id)[..]).await
+    }
+
+    /// Sends a state to all the lights of a group with a single request.
+    ///
+    /// Group `0` is a special group containing all the lights known to the bridge,
+    /// which makes `group_action(0, ...)` a cheaper alternative to `to_all`.
+    ///
+    /// As with `state_to`, errors for individual attributes are kept in the `ApiResponse`.
+    pub async fn group_action(&self, id: u8, new_state: &SendableState) -> Result<ApiResponse> {
+        self.send_to_resource(
+            &format!("./groups/{}/action", id)[..],
+            AllowedMethod::PUT,
+            Some(new_state),
+        )
+        .await
+    }
+
+    /// Get all the scenes stored on the bridge, mapped by their id.
+    ///
+    /// The bridge does not include the light states when listing scenes, use
+    /// `get_scene` to get them.
+    pub async fn get_scenes(&self) -> Result<BTreeMap<String, Scene>> {
+        self.get_resource("./scenes").await
+    }
+
+    /// Get a single scene, including its light states
+    pub async fn get_scene(&self, id: &str) -> Result<Scene> {
+        self.get_resource(&format!("./scenes/{}", id)[..]).await
+    }
+
+    /// Creates a new scene and returns its id
+    pub async fn create_scene(&self, scene: &NewScene) -> Result<String> {
+        self.create_resource("./scenes", scene).await
+    }
+
+    /// Sets the state a light in a scene will be recalled with
+    pub async fn set_scene_lightstate(
+        &self,
+        id: &str,
+        light: u8,
+        new_state: &SendableState,
+    ) -> Result<ApiResponse> {
+        self.send_to_resource(
+            &format!("./scenes/{}/lightstates/{}", id, light)[..],
+            AllowedMethod::PUT,
+            Some(new_state),
+        )
+        .await
+    }
+
+    /// Stores the current state of the lights of the scene into the scene
+    pub async fn store_scene(&self, id: &str) -> Result<ApiResponse> {
+        let body = serde_json::json!({ "storelightstate": true });
+        self.send_to_resource(
+            &format!("./scenes/{}", id)[..],
+            AllowedMethod::PUT,
+            Some(&body),
+        )
+        .await?
+        .into_result()
+    }
+
+    /// Recalls a scene through a group action.
+    ///
+    /// Only the lights of the scene that are also in the group change, group `0`
+    /// can be used to recall a light scene on all of its lights.
+    pub async fn recall_scene(&self, id: &str, group: u8) -> Result<ApiResponse> {
+        let body = serde_json::json!({ "scene": id });
+        self.send_to_resource(
+            &format!("./groups/{}/action", group)[..],
+            AllowedMethod::PUT,
+            Some(&body),
+        )
+        .await
+    }
+
+    /// Deletes a scene from the bridge
+    pub async fn delete_scene(&self, id: &str) -> Result<ApiResponse> {
+        self.delete_resource(&format!("./scenes/{}", id)[..]).await
+    }
+
+    /// Get all the sensors known to the bridge, mapped by their id
+    pub async fn get_sensors(&self) -> Result<BTreeMap<u8, Sensor>> {
+        self.get_resource("./sensors").await
+    }
+
+    /// Get a single sensor by its id
+    pub async fn get_sensor(&self, id: u8) -> Result<Sensor> {
+        self.get_resource(&format!("./sensors/{}", id)[..]).await
+    }
+
+    /// Renames a sensor
+    pub async fn rename_sensor(&self, id: u8, name: &str) -> Result<ApiResponse> {
+        let body = serde_json::json!({ "name": name });
+        self.send_to_resource(
+            &format!("./sensors/{}", id)[..],
+            AllowedMethod::PUT,
+            Some(&body),
+        )
+        .await?
+        .into_result()
+    }
+
+    /// Updates the configuration of a sensor, only the fields set in `config` are sent.
+    ///
+    /// Errors for individual attributes are kept in the `ApiResponse`.
+    pub async fn update_sensor_config(
+        &self,
+        id: u8,
+        config: &SensorConfigUpdate,
+    ) -> Result<ApiResponse> {
+        self.send_to_resource(
+            &format!("./sensors/{}/config", id)[..],
+            AllowedMethod::PUT,
+            Some(config),
+        )
+        .await
+    }
+
+    /// Deletes a sensor from the bridge
+    pub async fn delete_sensor(&self, id: u8) -> Result<ApiResponse> {
+        self.delete_resource(&format!("./sensors/{}", id)[..]).await
+    }
+
+    /// Get all the rules defined on the bridge, mapped by their id
+    pub async fn get_rules(&self) -> Result<BTreeMap<u8, Rule>> {
+        self.get_resource("./rules").await
+    }
+
+    /// Get a single rule by its id
+    pub async fn get_rule(&self, id: u8) -> Result<Rule> {
+        self.get_resource(&format!("./rules/{}", id)[..]).await
+    }
+
+    /// Creates a new rule and returns its id
+    pub async fn create_rule(&self, rule: &NewRule) -> Result<u8> {
+        self.create_numeric_resource("./rules", rule, "rule").await
+    }
+
+    /// Updates a rule, only the fields set in `update` are sent
+    pub async fn update_rule(&self, id: u8, update: &RuleUpdate) -> Result<ApiResponse> {
+        self.send_to_resource(
+            &format!("./rules/{}", id)[..],
+            AllowedMethod::PUT,
+            Some(update),
+        )
+        .await?
+        .into_result()
+    }
+
+    /// Deletes a rule from the bridge
+    pub async fn delete_rule(&self, id: u8) -> Result<ApiResponse> {
+        self.delete_resource(&format!("./rules/{}", id)[..]).await
+    }
+
+    /// Get all the schedules defined on the bridge, mapped by their id
+    pub async fn get_schedules(&self) -> Result<BTreeMap<u8, Schedule>> {
+        self.get_resource("./schedules").await
+    }
+
+    /// Get a single schedule by its id
+    pub async fn get_schedule(&self, id: u8) -> Result<Schedule> {
+        self.get_resource(&format!("./schedules/{}", id)[..]).await
+    }
+
+    /// Creates a new schedule and returns its id.
+    ///
+    /// A command address relative to the API root (`/lights/1/state`) is completed
+    /// with the token of this bridge.
+    pub async fn create_schedule(&self, schedule: &NewSchedule) -> Result<u8> {
+        let mut schedule = schedule.clone();
+        schedule.command = self.absolute_command(schedule.command);
+        self.create_numeric_resource("./schedules", &schedule, "schedule")
+            .await
+    }
+
+    /// Updates a schedule, only the fields set in `update` are sent.
+    ///
+    /// A command address relative to the API root is completed as in `create_schedule`.
+    pub async fn update_schedule(&self, id: u8, update: &ScheduleUpdate) -> Result<ApiResponse> {
+        let mut update = update.clone();
+        update.command = update.command.map(|command| self.absolute_command(command));
+        self.send_to_resource(
+            &format!("./schedules/{}", id)[..],
+            AllowedMethod::PUT,
+            Some(&update),
+        )
+        .await?
+        .into_result()
+    }
+
+    /// Deletes a schedule from the bridge
+    pub async fn delete_schedule(&self, id: u8) -> Result<ApiResponse> {
+        self.delete_resource(&format!("./schedules/{}", id)[..])
+            .await
+    }
+
+    /// Prefixes the address of a command with the API root of this bridge, unless
+    /// it already includes one
+    fn absolute_command(&self, mut command: Command) -> Command {
+        if !command.address.starts_with("/api/") {
+            command.address = format!(
+                "{}/{}",
+                self.target.path().trim_end_matches('/'),
+                command.address.trim_start_matches('/')
+            );
+        }
+        command
+    }
+
+    /// Get the configuration of the bridge
+    pub async fn get_config(&self) -> Result<BridgeConfig> {
+        self.get_resource("./config").await
+    }
+
+    /// Updates the configuration of the bridge, only the fields set in `update` are sent.
+    ///
+    /// Errors for individual attributes (e.g. an invalid timezone) are kept in the `ApiResponse`.
+    pub async fn update_config(&self, update: &ConfigUpdate) -> Result<ApiResponse> {
+        self.send_to_resource("./config", AllowedMethod::PUT, Some(update))
+            .await
+    }
+
+    /// Get the applications whitelisted on the bridge, mapped by their token
+    pub async fn get_whitelist(&self) -> Result<BTreeMap<String, WhitelistEntry>> {
+        Ok(self.get_config().await?.whitelist)
+    }
+
+    /// Removes a token from the whitelist of the bridge
+    pub async fn delete_whitelist_entry(&self, token: &str) -> Result<ApiResponse> {
+        self.delete_resource(&format!("./config/whitelist/{}", token)[..])
+            .await
+    }
+
+    /// Removes the tokens that have not been used for at least `days` days and
+    /// returns them. The token used by this bridge is never removed.
+    ///
+    /// The last use dates are compared with the current time of the bridge, entries
+    /// with dates the bridge did not report in the usual format are kept.
+    pub async fn prune_whitelist(&self, days: u32) -> Result<Vec<String>> {
+        let config = self.get_config().await?;
+        let now = config.utc.ok_or_else(|| {
+            Error::Deserialization(serde::de::Error::custom(
+                "the configuration did not contain the time of the bridge",
+            ))
+        })?;
+        let max_idle = Duration::from_secs(u64::from(days) * 24 * 60 * 60);
+
+        let mut pruned = Vec::new();
+        for (token, entry) in config.whitelist {
+            if token == self.token {
+                continue;
+            }
+            if matches!(entry.idle_time(&now), Some(idle) if idle >= max_idle) {
+                self.delete_whitelist_entry(&token).await?;
+                pruned.push(token);
+            }
+        }
+        Ok(pruned)
+    }
+
+    /// Starts a search for new lights, which runs for about 40 seconds.
+    ///
+    /// Lights that cannot be found by a regular search (e.g. reset elsewhere) can be
+    /// looked for by the serial numbers printed on them, up to 10 per search.
+    /// The cached lights are invalidated, so the next `get_lights` rescans.
+    pub async fn search_lights(&self, deviceids: &[&str]) -> Result<ApiResponse> {
+        let response = self.start_search("./lights", deviceids).await;
+        self.invalidate_lights();
+        response
+    }
+
+    /// Progress of the last search for lights and the lights it found
+    pub async fn get_new_lights(&self) -> Result<NewDevices> {
+        self.get_resource("./lights/new").await
+    }
+
+    /// Waits for the running search for lights to finish and returns the lights it found.
+    ///
+    /// Gives up with `Error::Timeout` if the bridge still reports the search as active
+    /// after `timeout`, `search::SEARCH_TIMEOUT` suits the usual searches. The cached
+    /// lights are invalidated once the search is over.
+    pub async fn wait_for_new_lights(&self, timeout: Duration) -> Result<NewDevices> {
+        let found = self.wait_for_search("./lights/new", timeout).await;
+        self.invalidate_lights();
+        found
+    }
+
+    /// Starts a search for new sensors, which runs for about 40 seconds.
+    ///
+    /// Same as `search_lights`, sensors can be looked for by their serial numbers.
+    pub async fn search_sensors(&self, deviceids: &[&str]) -> Result<ApiResponse> {
+        self.start_search("./sensors", deviceids).await
+    }
+
+    /// Progress of the last search for sensors and the sensors it found
+    pub async fn get_new_sensors(&self) -> Result<NewDevices> {
+        self.get_resource("./sensors/new").await
+    }
+
+    /// Waits for the running search for sensors to finish and returns the sensors it found.
+    ///
+    /// Gives up with `Error::Timeout` if the bridge still reports the search as active
+    /// after `timeout`, `search::SEARCH_TIMEOUT` suits the usual searches.
+    pub async fn wait_for_new_sensors(&self, timeout: Duration) -> Result<NewDevices> {
+        self.wait_for_search("./sensors/new", timeout).await
+    }
+
+    /// Starts a search on `/lights` or `/sensors`, optionally for given serial numbers
+    async fn start_search(&self, path: &str, deviceids: &[&str]) -> Result<ApiResponse> {
+        let body = if deviceids.is_empty() {
+            serde_json::json!({})
+        } else {
+            serde_json::json!({ "deviceid": deviceids })
+        };
+        self.send_to_resource(path, AllowedMethod::POST, Some(&body))
+            .await?
+            .into_result()
+    }
+
+    /// Polls `/lights/new` or `/sensors/new` until the bridge is done searching, or
+    /// `timeout` has passed
+    async fn wait_for_search(&self, path: &str, timeout: Duration) -> Result<NewDevices> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            let found: NewDevices = self.get_resource(path).await?;
+            if !found.is_active() {
+                return Ok(found);
+            }
+            let now = tokio::time::Instant::now();
+            if now >= deadline {
+                return Err(Error::Timeout(format!(
+                    "`{}` still searching after {:?}",
+                    path, timeout
+                )));
+            }
+            tokio::time::delay_for(SEARCH_POLL_INTERVAL.min(deadline - now)).await;
+        }
+    }
+
+    /// Drops the cached lights so they are fetched again when next needed
+    fn invalidate_lights(&self) {
+        let mut cached_lights = write_lock(&self.lights);
+        let mut cached_ids = write_lock(&self.light_ids);
+        *cached_lights = None;
+        *cached_ids = None;
     }
 
     /// Provided an endpoint string, and a method it will create a `RequestTarget` that can
     /// be sent a request. The final URI will depend on the `self.target` field and the string
     /// provided.
-    fn get_endpoint(&self, s: &str, method: AllowedMethod) -> RequestTarget {
-        (self.target.join(s).unwrap(), method)
+    fn get_endpoint(&self, s: &str, method: AllowedMethod) -> Result<RequestTarget> {
+        let url = self
+            .target
+            .join(s)
+            .map_err(|e| Error::InvalidTarget(format!("could not join `{}`: {}", s, e)))?;
+        Ok((url, method))
+    }
+
+    /// Fetches a resource from the bridge and decodes it
+    async fn get_resource<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
+        let endpoint = self.get_endpoint(path, AllowedMethod::GET)?;
+        parse_response(send_request(endpoint, None, &self.client).await?).await
+    }
+
+    /// Sends a body to a resource and decodes the per-attribute response
+    async fn send_to_resource<B: Serialize + ?Sized>(
+        &self,
+        path: &str,
+        method: AllowedMethod,
+        body: Option<&B>,
+    ) -> Result<ApiResponse> {
+        let endpoint = self.get_endpoint(path, method)?;
+        parse_api_response(send_body(endpoint, body, &self.client).await?).await
+    }
+
+    /// Creates a resource and returns the id the bridge assigned to it
+    async fn create_resource<B: Serialize + ?Sized>(&self, path: &str, body: &B) -> Result<String> {
+        let response = self
+            .send_to_resource(path, AllowedMethod::POST, Some(body))
+            .await?
+            .into_result()?;
+        response
+            .success_for("id")
+            .and_then(serde_json::Value::as_str)
+            .map(String::from)
+            .ok_or_else(|| {
+                Error::Deserialization(serde::de::Error::custom(
+                    "the response did not contain the id of the created resource",
+                ))
+            })
+    }
+
+    /// Creates a resource whose id is a number, `what` names it in the error
+    /// returned for any other id
+    async fn create_numeric_resource<B: Serialize + ?Sized>(
+        &self,
+        path: &str,
+        body: &B,
+        what: &str,
+    ) -> Result<u8> {
+        let id = self.create_resource(path, body).await?;
+        id.parse().map_err(|_| {
+            Error::Deserialization(serde::de::Error::custom(format!(
+                "the bridge returned a non numeric {} id `{}`",
+                what, id
+            )))
+        })
+    }
+
+    /// Deletes a resource, failing if the bridge reports an error
+    async fn delete_resource(&self, path: &str) -> Result<ApiResponse> {
+        self.send_to_resource::<()>(path, AllowedMethod::DELETE, None)
+            .await?
+            .into_result()
+    }
+
+    /// Print useful information about the state of your system
+    ///
+    /// Namely, asks for all available lights and print a JSON representation
+    /// of the system to STDOUT.
+    pub async fn system_info(&self) -> Result<()> {
+        let lights = self.scan().await?;
+        println!("{}", serde_json::to_string_pretty(&lights)?);
+        Ok(())
+    }
+}
+
+/// # Take it to the Bridge!
+///
+/// This is the Bridge object - the blocking entrypoint of the library.
+///
+/// This is the manager struct that implements all the core methods that are required to interact
+/// with the Hue Bridge. It has a bunch of convenience functions such as sending state, scanning for lights,
+/// getting the information about your existing lights and finding bridge IP addresses with SSDP.
+///
+/// It is a thin wrapper that drives an [AsyncBridge](struct.AsyncBridge.html) on its own runtime.
+/// If you are already inside of a tokio application use the `AsyncBridge` directly instead, as
+/// blocking on a runtime from within another one panics.
+///
+/// The bridge is `Send + Sync` and can be shared between threads behind an `Arc`, with all of
+/// them sending states and refreshing the light cache through the same client.
+///
+/// Additional features can be enabled:
+/// - `persist` - enables building a bridge from environment variables and serialising to file
+#[derive(Debug)]
+pub struct Bridge {
+    pub target: Url,
+    inner: AsyncBridge,
+    runtime: BridgeRuntime,
+}
+
+/// The runtime the blocking `Bridge` drives its requests on
+#[derive(Debug)]
+enum BridgeRuntime {
+    Owned(Runtime),
+    External(Handle),
+}
+
+impl BridgeRuntime {
+    fn handle(&self) -> &Handle {
+        match self {
+            Self::Owned(runtime) => runtime.handle(),
+            Self::External(handle) => handle,
+        }
+    }
+}
+
+impl Bridge {
+    /// Constructor for a bridge from and IP and a token
+    ///
+    /// Use the [BridgeBuilder](struct.BridgeBuilder.html) for more control.
+    pub fn new(ip: IpAddr, token: String) -> Result<Self> {
+        BridgeBuilder::new().ip(ip).token(token).build()
+    }
+
+    /// Creates a `BridgeBuilder`
+    pub fn builder() -> BridgeBuilder {
+        BridgeBuilder::new()
+    }
+
+    /// Wraps an existing `AsyncBridge` into a blocking one, creating a runtime for it
+    pub fn from_async(inner: AsyncBridge) -> Result<Self> {
+        Ok(Self::with_runtime(
+            inner,
+            BridgeRuntime::Owned(create_runtime()?),
+        ))
+    }
+
+    fn with_runtime(inner: AsyncBridge, runtime: BridgeRuntime) -> Self {
+        Bridge {
+            target: inner.target.clone(),
+            inner,
+            runtime,
+        }
+    }
+
+    /// Checks that the token of the bridge is whitelisted.
+    ///
+    /// See [AsyncBridge::validate](struct.AsyncBridge.html#method.validate).
+    pub fn validate(&self) -> Result<()> {
+        self.block_on(self.inner.validate())
+    }
+
+    /// Access the underlying `AsyncBridge`
+    pub fn as_async(&self) -> &AsyncBridge {
+        &self.inner
+    }
+
+    /// Runs a future from the `AsyncBridge` to completion on the bridge runtime.
+    ///
+    /// This goes through the runtime handle, so several threads can block on
+    /// requests of the same bridge at once.
+    fn block_on<F: std::future::Future>(&self, fut: F) -> F::Output {
+        self.runtime.handle().block_on(fut)
+    }
+
+    /// Get the lights from the bridge struct. It performs a rescan and
+    /// updates a private lights field in the Bridge if that has not been
+    /// done yet.
+    ///
+    /// This performs a clone rather than returning a reference.
+    pub fn get_lights(&self) -> Result<BTreeMap<u8, Light>> {
+        self.block_on(self.inner.get_lights())
+    }
+
+    /// Sends a state to a given light by its ID on the system.
+    ///
+    /// See [AsyncBridge::state_to](struct.AsyncBridge.html#method.state_to).
+    pub fn state_to(&self, id: u8, new_state: &SendableState) -> Result<ApiResponse> {
+        self.block_on(self.inner.state_to(id, new_state))
+    }
+
+    /// Sends a state to all lights in the system
+    ///
+    /// See [AsyncBridge::to_all](struct.AsyncBridge.html#method.to_all).
+    pub fn to_all(&self, new_state: &SendableState) -> Result<Vec<ApiResponse>> {
+        self.block_on(self.inner.to_all(new_state))
+    }
+
+    /// Send a state object to all lights on the network.
+    pub fn state_to_multiple<'a>(
+        &self,
+        ids: impl IntoIterator<Item = u8>,
+        new_states: impl IntoIterator<Item = &'a SendableState>,
+    ) -> Result<Vec<ApiResponse>> {
+        self.block_on(self.inner.state_to_multiple(ids, new_states))
+    }
+
+    /// Sends a state to all lights in the system, adapted to what each light can do.
+    ///
+    /// See [AsyncBridge::to_all_adapted](struct.AsyncBridge.html#method.to_all_adapted).
+    #[cfg(feature = "color")]
+    pub fn to_all_adapted(&self, new_state: &SendableState) -> Result<Vec<ApiResponse>> {
+        self.block_on(self.inner.to_all_adapted(new_state))
+    }
+
+    /// Sends states to several lights, each adapted to what its light can do.
+    #[cfg(feature = "color")]
+    pub fn state_to_multiple_adapted<'a>(
+        &self,
+        ids: impl IntoIterator<Item = u8>,
+        new_states: impl IntoIterator<Item = &'a SendableState>,
+    ) -> Result<Vec<ApiResponse>> {
+        self.block_on(self.inner.state_to_multiple_adapted(ids, new_states))
+    }
+
+    /// Renames a light, updating the cached lights
+    pub fn rename_light(&self, id: u8, name: &str) -> Result<ApiResponse> {
+        self.block_on(self.inner.rename_light(id, name))
+    }
+
+    /// Deletes a light from the bridge, removing it from the cached lights
+    pub fn delete_light(&self, id: u8) -> Result<ApiResponse> {
+        self.block_on(self.inner.delete_light(id))
+    }
+
+    /// Updates the configuration of a light, only the fields set in `config` are sent.
+    ///
+    /// See [AsyncBridge::update_light_config](struct.AsyncBridge.html#method.update_light_config).
+    pub fn update_light_config(&self, id: u8, config: &LightConfigUpdate) -> Result<ApiResponse> {
+        self.block_on(self.inner.update_light_config(id, config))
+    }
+
+    /// Get all the groups defined on the bridge, mapped by their id.
+    ///
+    /// See [AsyncBridge::get_groups](struct.AsyncBridge.html#method.get_groups).
+    pub fn get_groups(&self) -> Result<BTreeMap<u8, Group>> {
+        self.block_on(self.inner.get_groups())
+    }
+
+    /// Get a single group by its id
+    pub fn get_group(&self, id: u8) -> Result<Group> {
+        self.block_on(self.inner.get_group(id))
+    }
+
+    /// Creates a new group and returns its id
+    pub fn create_group(&self, group: &NewGroup) -> Result<u8> {
+        self.block_on(self.inner.create_group(group))
+    }
+
+    /// Renames a group
+    pub fn rename_group(&self, id: u8, name: &str) -> Result<ApiResponse> {
+        self.block_on(self.inner.rename_group(id, name))
+    }
+
+    /// Replaces the lights that are members of a group
+    pub fn set_group_lights(&self, id: u8, lights: &[u8]) -> Result<ApiResponse> {
+        self.block_on(self.inner.set_group_lights(id, lights))
+    }
+
+    /// Deletes a group from the bridge
+    pub fn delete_group(&self, id: u8) -> Result<ApiResponse> {
+        self.block_on(self.inner.delete_group(id))
+    }
+
+    /// Sends a state to all the lights of a group with a single request.
+    ///
+    /// See [AsyncBridge::group_action](struct.AsyncBridge.html#method.group_action).
+    pub fn group_action(&self, id: u8, new_state: &SendableState) -> Result<ApiResponse> {
+        self.block_on(self.inner.group_action(id, new_state))
+    }
+
+    /// Get all the scenes stored on the bridge, mapped by their id.
+    ///
+    /// See [AsyncBridge::get_scenes](struct.AsyncBridge.html#method.get_scenes).
+    pub fn get_scenes(&self) -> Result<BTreeMap<String, Scene>> {
+        self.block_on(self.inner.get_scenes())
+    }
+
+    /// Get a single scene, including its light states
+    pub fn get_scene(&self, id: &str) -> Result<Scene> {
+        self.block_on(self.inner.get_scene(id))
+    }
+
+    /// Creates a new scene and returns its id
+    pub fn create_scene(&self, scene: &NewScene) -> Result<String> {
+        self.block_on(self.inner.create_scene(scene))
+    }
+
+    /// Sets the state a light in a scene will be recalled with
+    pub fn set_scene_lightstate(
+        &self,
+        id: &str,
+        light: u8,
+        new_state: &SendableState,
+    ) -> Result<ApiResponse> {
+        self.block_on(self.inner.set_scene_lightstate(id, light, new_state))
+    }
+
+    /// Stores the current state of the lights of the scene into the scene
+    pub fn store_scene(&self, id: &str) -> Result<ApiResponse> {
+        self.block_on(self.inner.store_scene(id))
+    }
+
+    /// Recalls a scene through a group action.
+    ///
+    /// See [AsyncBridge::recall_scene](struct.AsyncBridge.html#method.recall_scene).
+    pub fn recall_scene(&self, id: &str, group: u8) -> Result<ApiResponse> {
+        self.block_on(self.inner.recall_scene(id, group))
+    }
+
+    /// Deletes a scene from the bridge
+    pub fn delete_scene(&self, id: &str) -> Result<ApiResponse> {
+        self.block_on(self.inner.delete_scene(id))
+    }
+
+    /// Get all the sensors known to the bridge, mapped by their id
+    pub fn get_sensors(&self) -> Result<BTreeMap<u8, Sensor>> {
+        self.block_on(self.inner.get_sensors())
+    }
+
+    /// Get a single sensor by its id
+    pub fn get_sensor(&self, id: u8) -> Result<Sensor> {
+        self.block_on(self.inner.get_sensor(id))
+    }
+
+    /// Renames a sensor
+    pub fn rename_sensor(&self, id: u8, name: &str) -> Result<ApiResponse> {
+        self.block_on(self.inner.rename_sensor(id, name))
+    }
+
+    /// Updates the configuration of a sensor, only the fields set in `config` are sent.
+    ///
+    /// See [AsyncBridge::update_sensor_config](struct.AsyncBridge.html#method.update_sensor_config).
+    pub fn update_sensor_config(&self, id: u8, config: &SensorConfigUpdate) -> Result<ApiResponse> {
+        self.block_on(self.inner.update_sensor_config(id, config))
+    }
+
+    /// Deletes a sensor from the bridge
+    pub fn delete_sensor(&self, id: u8) -> Result<ApiResponse> {
+        self.block_on(self.inner.delete_sensor(id))
+    }
+
+    /// Get all the rules defined on the bridge, mapped by their id
+    pub fn get_rules(&self) -> Result<BTreeMap<u8, Rule>> {
+        self.block_on(self.inner.get_rules())
+    }
+
+    /// Get a single rule by its id
+    pub fn get_rule(&self, id: u8) -> Result<Rule> {
+        self.block_on(self.inner.get_rule(id))
+    }
+
+    /// Creates a new rule and returns its id
+    pub fn create_rule(&self, rule: &NewRule) -> Result<u8> {
+        self.block_on(self.inner.create_rule(rule))
+    }
+
+    /// Updates a rule, only the fields set in `update` are sent
+    pub fn update_rule(&self, id: u8, update: &RuleUpdate) -> Result<ApiResponse> {
+        self.block_on(self.inner.update_rule(id, update))
+    }
+
+    /// Deletes a rule from the bridge
+    pub fn delete_rule(&self, id: u8) -> Result<ApiResponse> {
+        self.block_on(self.inner.delete_rule(id))
+    }
+
+    /// Get all the schedules defined on the bridge, mapped by their id
+    pub fn get_schedules(&self) -> Result<BTreeMap<u8, Schedule>> {
+        self.block_on(self.inner.get_schedules())
+    }
+
+    /// Get a single schedule by its id
+    pub fn get_schedule(&self, id: u8) -> Result<Schedule> {
+        self.block_on(self.inner.get_schedule(id))
+    }
+
+    /// Creates a new schedule and returns its id
+    ///
+    /// See [AsyncBridge::create_schedule](struct.AsyncBridge.html#method.create_schedule).
+    pub fn create_schedule(&self, schedule: &NewSchedule) -> Result<u8> {
+        self.block_on(self.inner.create_schedule(schedule))
+    }
+
+    /// Updates a schedule, only the fields set in `update` are sent
+    pub fn update_schedule(&self, id: u8, update: &ScheduleUpdate) -> Result<ApiResponse> {
+        self.block_on(self.inner.update_schedule(id, update))
+    }
+
+    /// Deletes a schedule from the bridge
+    pub fn delete_schedule(&self, id: u8) -> Result<ApiResponse> {
+        self.block_on(self.inner.delete_schedule(id))
+    }
+
+    /// Get the configuration of the bridge
+    pub fn get_config(&self) -> Result<BridgeConfig> {
+        self.block_on(self.inner.get_config())
+    }
+
+    /// Updates the configuration of the bridge, only the fields set in `update` are sent.
+    ///
+    /// See [AsyncBridge::update_config](struct.AsyncBridge.html#method.update_config).
+    pub fn update_config(&self, update: &ConfigUpdate) -> Result<ApiResponse> {
+        self.block_on(self.inner.update_config(update))
+    }
+
+    /// Get the applications whitelisted on the bridge, mapped by their token
+    pub fn get_whitelist(&self) -> Result<BTreeMap<String, WhitelistEntry>> {
+        self.block_on(self.inner.get_whitelist())
+    }
+
+    /// Removes a token from the whitelist of the bridge
+    pub fn delete_whitelist_entry(&self, token: &str) -> Result<ApiResponse> {
+        self.block_on(self.inner.delete_whitelist_entry(token))
+    }
+
+    /// Removes the tokens that have not been used for at least `days` days and
+    /// returns them. The token used by this bridge is never removed.
+    ///
+    /// See [AsyncBridge::prune_whitelist](struct.AsyncBridge.html#method.prune_whitelist).
+    pub fn prune_whitelist(&self, days: u32) -> Result<Vec<String>> {
+        self.block_on(self.inner.prune_whitelist(days))
+    }
+
+    /// Starts a search for new lights, which runs for about 40 seconds.
+    ///
+    /// See [AsyncBridge::search_lights](struct.AsyncBridge.html#method.search_lights).
+    pub fn search_lights(&self, deviceids: &[&str]) -> Result<ApiResponse> {
+        self.block_on(self.inner.search_lights(deviceids))
+    }
+
+    /// Progress of the last search for lights and the lights it found
+    pub fn get_new_lights(&self) -> Result<NewDevices> {
+        self.block_on(self.inner.get_new_lights())
+    }
+
+    /// Blocks until the running search for lights is finished and returns the lights it found.
+    ///
+    /// See [AsyncBridge::wait_for_new_lights](struct.AsyncBridge.html#method.wait_for_new_lights).
+    pub fn wait_for_new_lights(&self, timeout: Duration) -> Result<NewDevices> {
+        self.block_on(self.inner.wait_for_new_lights(timeout))
+    }
+
+    /// Starts a search for new sensors, which runs for about 40 seconds.
+    ///
+    /// See [AsyncBridge::search_sensors](struct.AsyncBridge.html#method.search_sensors).
+    pub fn search_sensors(&self, deviceids: &[&str]) -> Result<ApiResponse> {
+        self.block_on(self.inner.search_sensors(deviceids))
+    }
+
+    /// Progress of the last search for sensors and the sensors it found
+    pub fn get_new_sensors(&self) -> Result<NewDevices> {
+        self.block_on(self.inner.get_new_sensors())
+    }
+
+    /// Blocks until the running search for sensors is finished and returns the sensors it found.
+    ///
+    /// See [AsyncBridge::wait_for_new_sensors](struct.AsyncBridge.html#method.wait_for_new_sensors).
+    pub fn wait_for_new_sensors(&self, timeout: Duration) -> Result<NewDevices> {
+        self.block_on(self.inner.wait_for_new_sensors(timeout))
     }
 
     /// Method to interactively register a new bridge.
@@ -180,34 +1323,27 @@ impl Bridge {
     /// returned token is that a user might want to store a token, however the struct
     /// field is private by default on the bridge, so we expose the token upon registration
     /// for the user to store it as they might see fit.
-    pub fn try_register(interactive: bool) -> Result<(Self, String), String> {
+    pub fn try_register(interactive: bool) -> Result<(Self, String)> {
         use serde_json::Value;
 
         let client = reqwest::Client::new();
-        let mut runtime = tokio::runtime::Builder::new()
-            .basic_scheduler()
-            .enable_all()
-            .build()
-            .expect("Could not create tokio runtime during registration");
+        let mut runtime = create_runtime()?;
 
-        let bridges = Self::find_bridges(); // find any bridges present on network
+        let bridges = Self::find_bridges()?; // find any bridges present on network
 
         if interactive {
             println!("Found the following bridges:\n{:#?}", bridges);
         }
 
         let body = serde_json::json!({ "devicetype": "lighthouse" });
-        let mut check = |ip: IpAddr| -> Value {
+        let mut check = |ip: IpAddr| -> Result<Value> {
             runtime.block_on(async {
-                client
+                let response = client
                     .post(&format!("http://{}/api", ip))
                     .json(&body)
                     .send()
-                    .await
-                    .unwrap()
-                    .json()
-                    .await
-                    .unwrap()
+                    .await?;
+                Ok(response.json().await?)
             })
         };
 
@@ -215,7 +1351,9 @@ impl Bridge {
         let mut response;
 
         if bridges.is_empty() {
-            return Err(String::from("Could not find any bridges on the network"));
+            return Err(Error::Discovery(String::from(
+                "Could not find any bridges on the network",
+            )));
         } else {
             if interactive {
                 println!("Will try to register. Please press the connection button on your bridge");
@@ -226,7 +1364,7 @@ impl Bridge {
                 }
                 std::thread::sleep(std::time::Duration::from_secs(3));
                 for ip in &bridges {
-                    response = check(*ip);
+                    response = check(*ip)?;
                     if response[0]["error"]["type"] == 101 {
                         continue;
                     } else {
@@ -237,28 +1375,41 @@ impl Bridge {
             }
         }
 
-        let token = response[0]["success"]["username"].to_string();
-        let target = generate_target(*bridge_ip, &token)
-            .expect("Could not create the required target after registration");
+        let error = &response[0]["error"];
+        if !error.is_null() {
+            return Err(Error::from_api_value(error));
+        }
+
+        let token = response[0]["success"]["username"]
+            .as_str()
+            .ok_or_else(|| {
+                Error::Deserialization(serde::de::Error::custom(
+                    "registration response did not contain a username",
+                ))
+            })?
+            .to_owned();
+        let inner = BridgeBuilder::new()
+            .ip(*bridge_ip)
+            .token(token.clone())
+            .client(client)
+            .assemble()?;
 
         Ok((
-            Bridge {
-                target,
-                ip: *bridge_ip,
-                token: token.clone(),
-                client,
-                runtime: RefCell::new(runtime),
-                lights: RefCell::new(None),
-                light_ids: RefCell::new(None),
-            },
+            Self::with_runtime(inner, BridgeRuntime::Owned(runtime)),
             token,
         ))
     }
 
+    /// Fetches the part of the configuration the bridge shares without a token,
+    /// enough to identify it before registering.
+    pub fn public_config(ip: IpAddr) -> Result<PublicConfig> {
+        create_runtime()?.block_on(AsyncBridge::public_config(ip))
+    }
+
     /// Method to find bridge IP addressed on the network.
     ///
     /// If multiple are found, they are all returned.
-    pub fn find_bridges() -> Vec<IpAddr> {
+    pub fn find_bridges() -> Result<Vec<IpAddr>> {
         use ssdp::header::{HeaderMut, Man, MX, ST};
         use ssdp::message::{Multicast, SearchRequest};
 
@@ -274,7 +1425,7 @@ impl Bridge {
         // Find devices
         let devices = request
             .multicast()
-            .expect("Could not perform multicast request");
+            .map_err(|e| Error::Discovery(format!("Could not perform multicast request: {}", e)))?;
 
         // Coerce this into a vector
         let mut result: Vec<IpAddr> = devices.into_iter().map(|(_, src)| src.ip()).collect();
@@ -282,16 +1433,15 @@ impl Bridge {
         result.sort(); // TODO: see if this is necessary
         result.dedup();
 
-        result
+        Ok(result)
     }
 
     /// Print useful information about the state of your system
     ///
     /// Namely, asks for all available lights and print a JSON representation
     /// of the system to STDOUT.
-    pub fn system_info(&self) {
-        let lights = self.scan();
-        println!("{}", serde_json::to_string_pretty(&lights).unwrap());
+    pub fn system_info(&self) -> Result<()> {
+        self.block_on(self.inner.system_info())
     }
 
     /// Conditional feature:
@@ -300,31 +1450,42 @@ impl Bridge {
     /// variables.
     ///
     /// The variables that will be looked up are:
-    /// - HUE_BRIDGE_IP - the IP of the bridge on the local network
+    /// - HUE_BRIDGE_IP - the IP (or hostname, or `scheme://host:port`) of the bridge on the local network
     /// - HUE_BRIDGE_KEY - the KEY that you get when you register to the bridge.
     #[cfg(feature = "persist")]
-    pub fn from_env() -> Bridge {
-        let ip = std::env::var("HUE_BRIDGE_IP")
-            .expect("Could not find `HUE_BRIDGE_IP` environment variable.")
-            .parse()
-            .expect("Could not parse the address in the variable: HUE_BRIDGE_IP.");
-        let key = std::env::var("HUE_BRIDGE_KEY")
-            .expect("Could not find `HUE_BRIDGE_KEY` environment variable.");
+    pub fn from_env() -> Result<Bridge> {
+        let address = std::env::var("HUE_BRIDGE_IP").map_err(|_| {
+            Error::Persistence(String::from(
+                "Could not find `HUE_BRIDGE_IP` environment variable.",
+            ))
+        })?;
+        let key = std::env::var("HUE_BRIDGE_KEY").map_err(|_| {
+            Error::Persistence(String::from(
+                "Could not find `HUE_BRIDGE_KEY` environment variable.",
+            ))
+        })?;
 
-        Bridge::new(ip, key).expect(&format!("Could not create new bridge (IP {})", ip)[..])
+        builder_from_address(&address)?.token(key).build()
     }
 
     /// Conditional feature:
     ///
     /// Allows serializing the bridge to a text file. It is super simple
-    /// and basically ends up writing out the bridge target to a text file.
+    /// and basically ends up writing out the bridge address and token to a text file.
     ///
     /// TODO: Move to using serde serialization
     #[cfg(feature = "persist")]
-    pub fn to_file(&self, filename: &str) -> std::io::Result<()> {
+    pub fn to_file(&self, filename: &str) -> Result<()> {
         use std::io::prelude::*;
+        let target = &self.inner.target;
+        let host = target.host_str().unwrap_or_default();
+        // plain bridges keep the original format of just the IP
+        let address = match (target.scheme(), target.port()) {
+            ("http", None) => host.to_owned(),
+            (scheme, port) => format!("{}://{}:{}", scheme, host, port.unwrap_or(443)),
+        };
         let mut file = std::fs::File::create(filename)?;
-        file.write_all(format!("{}\n{}", self.ip, self.token).as_ref())?;
+        file.write_all(format!("{}\n{}", address, self.inner.token).as_ref())?;
         Ok(())
     }
 
@@ -332,22 +1493,72 @@ impl Bridge {
     ///
     /// Allows loading a Bridge from a text file.
     #[cfg(feature = "persist")]
-    pub fn from_file(filename: &str) -> std::io::Result<Self> {
+    pub fn from_file(filename: &str) -> Result<Self> {
         use std::io::{BufRead, BufReader};
         let file = std::fs::File::open(filename)?;
         let reader = BufReader::new(file);
 
-        let lines: Vec<String> = reader
-            .lines()
-            .enumerate()
-            .map(|(idx, line)| line.unwrap_or_else(|_| format!("Could not read line {}", idx)))
-            .collect();
+        let lines = reader.lines().collect::<std::io::Result<Vec<String>>>()?;
 
-        assert!(lines.len() == 2);
-        Ok(Bridge::new(
-            lines[0].parse().expect("Could not parse the provided IP"),
-            lines[1].clone(),
-        )
-        .expect("Could not create Bridge"))
+        if lines.len() != 2 {
+            return Err(Error::Persistence(format!(
+                "Expected 2 lines (address and key) in `{}`, found {}",
+                filename,
+                lines.len()
+            )));
+        }
+        builder_from_address(&lines[0])?
+            .token(lines[1].clone())
+            .build()
+    }
+}
+
+/// Parses a persisted bridge address. It can be a plain IP, a hostname or a
+/// `scheme://host:port` URL.
+#[cfg(feature = "persist")]
+fn builder_from_address(address: &str) -> Result<BridgeBuilder> {
+    if let Ok(ip) = address.parse() {
+        return Ok(BridgeBuilder::new().ip(ip));
     }
+    if !address.contains("://") {
+        return Ok(BridgeBuilder::new().host(address));
+    }
+    let url = Url::parse(address).map_err(|e| {
+        Error::Persistence(format!("Could not parse the address `{}`: {}", address, e))
+    })?;
+    let mut builder = BridgeBuilder::new()
+        .scheme(url.scheme())
+        .host(url.host_str().unwrap_or_default());
+    if let Some(port) = url.port() {
+        builder = builder.port(port);
+    }
+    Ok(builder)
+}
+
+/// How often the progress of a search for new devices is checked
+const SEARCH_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Creates the runtime that drives the requests of the blocking API.
+///
+/// A threaded scheduler is used so that the IO driver keeps running while
+/// several threads block on the runtime handle.
+fn create_runtime() -> Result<Runtime> {
+    tokio::runtime::Builder::new()
+        .threaded_scheduler()
+        .core_threads(1)
+        .enable_all()
+        .build()
+        .map_err(|e| Error::Runtime(e.to_string()))
+}
+
+/// Reads a cache lock. A panic while the lock was held cannot leave the cache half
+/// written (it is only ever replaced as a whole), so poisoning is ignored.
+fn read_lock<T>(lock: &RwLock<T>) -> RwLockReadGuard<'_, T> {
+    lock.read().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Writes a cache lock, ignoring poisoning for the same reason as `read_lock`
+fn write_lock<T>(lock: &RwLock<T>) -> RwLockWriteGuard<'_, T> {
+    lock.write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
 }
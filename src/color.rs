@@ -1,18 +1,55 @@
-/// # Color module (UNDERDEVELOPED)
+/// # Color module
 ///
-/// This module (gated under the `color` feature) contains helpers in converting
-/// colors to the required representations for the HUE API.
+/// This module (gated under the `color` feature) converts colors between the
+/// representations people use and the ones of the HUE API:
 ///
-/// **NOTE:** Currently untested and work in progress. If you want to please submit
-/// a PR with improvements.
-use palette::{rgb::Srgb, Hsl};
+/// - 'rgb', hex and 'hsl' to 'xy' (optionally clamped into a light's gamut) or
+///   `hue`/`sat`, and back again to display the state of a light
+/// - color temperatures between Kelvin, mireds and points of the Planckian locus
+/// - [`Color`], parsed from user input such as `#ff8800`, `2700K` or CSS names
+/// - perceptual fades between colors and brightnesses
+use crate::{
+    error::{Error, Result},
+    lights::{Capabilities, ColorMode, Gamut, SendableState, State},
+};
+use palette::{rgb::Srgb, Hsl, Hsv, Lab, Lch, Limited, LinSrgb, Mix};
+use std::{convert::TryFrom, str::FromStr};
+
+/// Chromaticity of the D65 white point
+const WHITE_POINT: [f32; 2] = [0.312_7, 0.329_0];
 
 /// Convert from 'rgb' to the 'xy' values that can be sent to the
-/// hue lights. Does not internally use color gamut.
+/// hue lights. Does not internally use color gamut, see [`rgb_to_xy_in_gamut`]
+/// to get a color the light is able to produce.
+///
+/// This was implemented based on the gist found
+/// [here](https://gist.github.com/popcorn245/30afa0f98eea1c2fd34d).
+pub fn rgb_to_xy(rgb: [u8; 3]) -> [f32; 2] {
+    let [x, y, z] = rgb_to_xyz(rgb);
+    chromaticity(x, y, z)
+}
+
+/// Convert from 'rgb' to the 'xy' values and the brightness to send to a light
+/// with the given gamut.
 ///
-/// **NOTE:** Currently no gamma correction is used. This was implemented based on the
-/// gist found [here](https://gist.github.com/popcorn245/30afa0f98eea1c2fd34d).
-pub fn rgb_to_xy(rgb: Vec<u8>) -> [f32; 2] {
+/// Colors outside of the gamut are moved to the closest color of the triangle. The
+/// brightness is the luminance (`Y`) of the color scaled to `0..=254`, black is `0`
+/// which lights do not accept, turn them off instead.
+///
+/// ```
+/// use lighthouse::{color::rgb_to_xy_in_gamut, lights::Gamut};
+/// let (xy, bri) = rgb_to_xy_in_gamut([0, 255, 0], &Gamut::B);
+/// assert!(Gamut::B.contains(xy));
+/// assert_eq!(bri, 170);
+/// ```
+pub fn rgb_to_xy_in_gamut(rgb: [u8; 3], gamut: &Gamut) -> ([f32; 2], u8) {
+    let [x, y, z] = rgb_to_xyz(rgb);
+    let bri = (y * 254.0).round().clamp(0.0, 254.0) as u8;
+    (gamut.closest(chromaticity(x, y, z)), bri)
+}
+
+/// CIE XYZ values of a gamma corrected 'rgb' color
+fn rgb_to_xyz(rgb: [u8; 3]) -> [f32; 3] {
     // NOTE: more information https://gist.github.com/popcorn245/30afa0f98eea1c2fd34d
     let standardise = |c: u8| {
         let val = (c as f32) / 255.0;
@@ -22,27 +59,33 @@ pub fn rgb_to_xy(rgb: Vec<u8>) -> [f32; 2] {
             val / 12.92
         }
     };
+    let (red, green, blue) = (
+        standardise(rgb[0]),
+        standardise(rgb[1]),
+        standardise(rgb[2]),
+    );
 
-    let cnv: Vec<f32> = rgb.into_iter().map(standardise).collect();
-    let (red, green, blue) = (cnv[0], cnv[1], cnv[2]);
+    [
+        red * 0.664_511 + green * 0.154_324 + blue * 0.162_028,
+        red * 0.283_881 + green * 0.668_433 + blue * 0.047_685,
+        red * 0.000_088 + green * 0.072_310 + blue * 0.986_039,
+    ]
+}
 
-    let x = red * 0.664_511 + green * 0.154_324 + blue * 0.162_028;
-    let y = red * 0.283_881 + green * 0.668_433 + blue * 0.047_685;
-    let z = red * 0.000_088 + green * 0.072_310 + blue * 0.986_039;
+/// The 'xy' chromaticity of CIE XYZ values
+fn chromaticity(x: f32, y: f32, z: f32) -> [f32; 2] {
     let denominator = x + y + z;
-
-    // TODO: if the z is truly the brightness we need to return it
+    if denominator == 0.0 {
+        // black has no chromaticity, so fall back to the white point
+        return WHITE_POINT;
+    }
     [x / denominator, y / denominator]
 }
 
 /// Convert from 'rgb' to the 'hsl' values that can be sent to the
 /// hue lights.
-pub fn rgb_to_hsl(rgb: Vec<u8>) -> (u16, u8, u8) {
-    let standard: Vec<f32> = rgb
-        .into_iter()
-        .map(|val: u8| (val as f32) / 255.0)
-        .collect();
-    let (red, green, blue) = (standard[0], standard[1], standard[2]);
+pub fn rgb_to_hsl(rgb: [u8; 3]) -> (u16, u8, u8) {
+    let [red, green, blue] = rgb.map(|val| (val as f32) / 255.0);
     let hsl: Hsl = Srgb::new(red, green, blue).into();
     let (h, s, l) = hsl.into_components();
     (
@@ -53,15 +96,605 @@ pub fn rgb_to_hsl(rgb: Vec<u8>) -> (u16, u8, u8) {
 }
 
 /// Convert hex color to `hsl`
-pub fn hex_to_hsl(s: &str) -> Result<(u16, u8, u8), std::num::ParseIntError> {
-    let rgb = hex_to_rgb(s)?;
-    Ok(rgb_to_hsl(rgb))
+pub fn hex_to_hsl(s: &str) -> Result<(u16, u8, u8)> {
+    hex_to_rgb(s).map(rgb_to_hsl)
+}
+
+/// Convert hex color string to `rgb`. A leading `#` is accepted.
+pub fn hex_to_rgb(s: &str) -> Result<[u8; 3]> {
+    let hex = s.trim_start_matches('#');
+    if hex.len() != 6 || !hex.is_ascii() {
+        return Err(Error::InvalidColor(format!(
+            "`{}` is not a 6 digit hex color",
+            s
+        )));
+    }
+    let channel = |i: usize| {
+        u8::from_str_radix(&hex[i..i + 2], 16)
+            .map_err(|e| Error::InvalidColor(format!("`{}`: {}", s, e)))
+    };
+    Ok([channel(0)?, channel(2)?, channel(4)?])
+}
+
+/// Convert `rgb` to a hex color string with a leading `#`
+pub fn rgb_to_hex(rgb: [u8; 3]) -> String {
+    let [red, green, blue] = rgb;
+    format!("#{:02x}{:02x}{:02x}", red, green, blue)
+}
+
+/// Convert 'xy' values and a brightness back to 'rgb', the inverse of
+/// [`rgb_to_xy_in_gamut`].
+///
+/// Colors brighter than what 'rgb' can hold keep their hue and are scaled down.
+pub fn xy_to_rgb(xy: [f32; 2], bri: u8) -> [u8; 3] {
+    let [x, y] = xy;
+    if y <= 0.0 {
+        return [0, 0, 0];
+    }
+    let luminance = f32::from(bri) / 254.0;
+    let (big_x, big_z) = (x * luminance / y, (1.0 - x - y) * luminance / y);
+
+    let linear = [
+        big_x * 1.656_494 - luminance * 0.354_852 - big_z * 0.255_038,
+        -big_x * 0.707_196 + luminance * 1.655_399 + big_z * 0.036_153,
+        big_x * 0.051_714 - luminance * 0.121_365 + big_z * 1.011_53,
+    ];
+    let max = linear.iter().copied().fold(1.0, f32::max);
+    linear.map(|c| gamma_encode(c / max))
+}
+
+/// Convert the `hue` and `sat` of a light and its brightness to 'rgb'
+pub fn hs_to_rgb(hue: u16, sat: u8, bri: u8) -> [u8; 3] {
+    let hsv = Hsv::new(
+        f32::from(hue) / 65535.0 * 360.0,
+        f32::from(sat) / 254.0,
+        f32::from(bri) / 254.0,
+    );
+    srgb_to_array(hsv.into())
+}
+
+/// Convert 'hsl' values back to 'rgb', the inverse of [`rgb_to_hsl`]
+pub fn hsl_to_rgb(hue: u16, sat: u8, lightness: u8) -> [u8; 3] {
+    let hsl = Hsl::new(
+        f32::from(hue) / 65535.0 * 360.0,
+        f32::from(sat) / 254.0,
+        f32::from(lightness) / 254.0,
+    );
+    srgb_to_array(hsl.into())
+}
+
+/// Convert a color temperature in Kelvin to mireds
+pub fn kelvin_to_mired(kelvin: u32) -> u16 {
+    (1_000_000.0 / kelvin.max(1) as f32)
+        .round()
+        .min(f32::from(u16::MAX)) as u16
+}
+
+/// Convert a color temperature in mireds to Kelvin
+pub fn mired_to_kelvin(mired: u16) -> u32 {
+    (1_000_000.0 / f32::from(mired.max(1))).round() as u32
 }
 
-/// Convert hex color string to `rgb`
-pub fn hex_to_rgb(s: &str) -> Result<Vec<u8>, std::num::ParseIntError> {
-    (0..s.len())
-        .step_by(2)
-        .map(|i| u8::from_str_radix(&s[i..i + 2], 16))
+/// Convert a color temperature in Kelvin to the closest mireds a light accepts.
+///
+/// Lights that do not report their range are clamped to the 153 to 500 mireds
+/// (2000K to 6500K) every white ambiance light supports.
+///
+/// ```
+/// use lighthouse::{color::kelvin_to_mired_for, lights::Capabilities};
+/// let capabilities: Capabilities =
+///     serde_json::from_str(r#"{"control": {"ct": {"min": 153, "max": 454}}}"#).unwrap();
+/// assert_eq!(kelvin_to_mired_for(2700, &capabilities), 370);
+/// assert_eq!(kelvin_to_mired_for(1800, &capabilities), 454);
+/// ```
+pub fn kelvin_to_mired_for(kelvin: u32, capabilities: &Capabilities) -> u16 {
+    let (min, max) = capabilities
+        .control
+        .ct
+        .map_or((153, 500), |range| (range.min, range.max));
+    kelvin_to_mired(kelvin).clamp(min, max.max(min))
+}
+
+/// 'xy' of a black body at a temperature in Kelvin, to match color lights with
+/// white ambiance lights.
+///
+/// The Planckian locus is approximated from 1667K to 25000K, temperatures outside of
+/// this range are clamped.
+pub fn kelvin_to_xy(kelvin: u32) -> [f32; 2] {
+    planckian_xy(f64::from(kelvin))
+}
+
+/// 'xy' of a black body at a color temperature in mireds
+pub fn mired_to_xy(mired: u16) -> [f32; 2] {
+    planckian_xy(1_000_000.0 / f64::from(mired.max(1)))
+}
+
+/// Point of the Planckian locus for a color temperature in Kelvin, using the
+/// cubic spline approximation of Kim et al. valid from 1667K to 25000K
+pub(crate) fn planckian_xy(kelvin: f64) -> [f32; 2] {
+    let kelvin = kelvin.clamp(1667.0, 25000.0);
+    let (t, t2, t3) = (kelvin, kelvin.powi(2), kelvin.powi(3));
+    let x = if kelvin <= 4000.0 {
+        -0.266_123_9e9 / t3 - 0.234_358_9e6 / t2 + 0.877_695_6e3 / t + 0.179_910
+    } else {
+        -3.025_846_9e9 / t3 + 2.107_037_9e6 / t2 + 0.222_634_7e3 / t + 0.240_390
+    };
+    let (x2, x3) = (x.powi(2), x.powi(3));
+    let y = if kelvin <= 2222.0 {
+        -1.106_381_4 * x3 - 1.348_110_20 * x2 + 2.185_558_32 * x - 0.202_196_83
+    } else if kelvin <= 4000.0 {
+        -0.954_947_6 * x3 - 1.374_185_93 * x2 + 2.091_370_15 * x - 0.167_488_67
+    } else {
+        3.081_758_0 * x3 - 5.873_386_70 * x2 + 3.751_129_97 * x - 0.370_014_83
+    };
+    [x as f32, y as f32]
+}
+
+/// Correlated color temperature in Kelvin of an 'xy' color, using McCamy's
+/// approximation.
+///
+/// The result is only meaningful for colors close to white, roughly between 2000K
+/// and 12500K. Colors past the turning point of the approximation, such as
+/// saturated reds, end up at its warmest temperature of about 1620K.
+///
+/// ```
+/// use lighthouse::color::xy_to_kelvin;
+/// let kelvin = xy_to_kelvin([0.3127, 0.329]);
+/// assert!((6400..6600).contains(&kelvin));
+/// ```
+pub fn xy_to_kelvin(xy: [f32; 2]) -> u32 {
+    let [x, y] = xy;
+    let n = ((x - 0.3320) / (0.1858 - y)).max(-1.28);
+    let kelvin = 449.0 * n.powi(3) + 3525.0 * n.powi(2) + 6823.3 * n + 5520.33;
+    kelvin.round().max(0.0) as u32
+}
+
+/// Approximate 'rgb' of a white with a color temperature in mireds
+pub fn mired_to_rgb(mired: u16, bri: u8) -> [u8; 3] {
+    xy_to_rgb(mired_to_xy(mired), bri)
+}
+
+/// Hex color of a light, worked out from its `colormode`.
+///
+/// Lights that are off are black, lights without a color are white.
+///
+/// ```
+/// use lighthouse::{color::state_to_hex, lights::State};
+/// let state: State = serde_json::from_str(
+///     r#"{"on": true, "bri": 254, "hue": 0, "sat": 254, "colormode": "hs"}"#,
+/// ).unwrap();
+/// assert_eq!(state_to_hex(&state), "#ff0000");
+/// ```
+pub fn state_to_hex(state: &State) -> String {
+    let bri = state.bri.unwrap_or(254);
+    let rgb = if !state.on {
+        [0, 0, 0]
+    } else {
+        match (state.colormode, state.xy, state.hue, state.ct) {
+            (Some(ColorMode::Hs), _, Some(hue), _) => hs_to_rgb(hue, state.sat.unwrap_or(254), bri),
+            (Some(ColorMode::Ct), _, _, Some(ct)) => {
+                mired_to_rgb(u16::try_from(ct).unwrap_or(u16::MAX), bri)
+            }
+            (_, Some(xy), _, _) => xy_to_rgb(xy, bri),
+            _ => [gamma_encode(f32::from(bri) / 254.0); 3],
+        }
+    };
+    rgb_to_hex(rgb)
+}
+
+/// 8 bit 'rgb' channel of a linear value in `0.0..=1.0`
+fn gamma_encode(c: f32) -> u8 {
+    let c = c.max(0.0);
+    let val = if c <= 0.003_130_8 {
+        12.92 * c
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    };
+    (val * 255.0).round().clamp(0.0, 255.0) as u8
+}
+
+fn srgb_to_array(rgb: Srgb) -> [u8; 3] {
+    let (red, green, blue) = rgb.into_components();
+    [red, green, blue].map(|c| (c * 255.0).round().clamp(0.0, 255.0) as u8)
+}
+
+/// A color as written by a person, to be sent to lights.
+///
+/// Parsed from `#rgb` and `#rrggbb` hex strings, `rgb(255, 136, 0)` and
+/// `hsl(32, 100%, 50%)` functions, CSS color names and color temperatures such as
+/// `2700K`.
+///
+/// ```
+/// use lighthouse::{color::Color, lights::SendableState};
+/// let orange: Color = "#f80".parse().unwrap();
+/// assert_eq!(orange, Color::Rgb(255, 136, 0));
+/// assert_eq!("rgb(255, 136, 0)".parse::<Color>().unwrap(), orange);
+/// assert!("Warm white".parse::<Color>().is_err());
+///
+/// let state = SendableState::from("2700K".parse::<Color>().unwrap());
+/// assert_eq!(state.ct, Some(370));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Rgb(u8, u8, u8),
+    /// A white of a color temperature in Kelvin
+    Temperature(u32),
+}
+
+impl Color {
+    /// Red, green and blue channels, approximated for temperatures
+    pub fn to_rgb(self) -> [u8; 3] {
+        match self {
+            Color::Rgb(red, green, blue) => [red, green, blue],
+            Color::Temperature(kelvin) => xy_to_rgb(kelvin_to_xy(kelvin), 254),
+        }
+    }
+}
+
+impl std::fmt::Display for Color {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Color::Rgb(red, green, blue) => write!(f, "#{:02x}{:02x}{:02x}", red, green, blue),
+            Color::Temperature(kelvin) => write!(f, "{}K", kelvin),
+        }
+    }
+}
+
+impl FromStr for Color {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::InvalidColor(format!("`{}` is not a known color", s));
+        let color = s.trim().to_ascii_lowercase();
+
+        if let Some(hex) = color.strip_prefix('#') {
+            let hex = match hex.len() {
+                3 => hex.chars().flat_map(|c| vec![c, c]).collect(),
+                _ => hex.to_owned(),
+            };
+            let [red, green, blue] = hex_to_rgb(&hex)?;
+            return Ok(Color::Rgb(red, green, blue));
+        }
+        // names such as "black" or "hotpink" end with a 'k' too
+        let kelvin = color.strip_suffix('k').map(str::trim);
+        if let Some(kelvin) =
+            kelvin.filter(|k| !k.is_empty() && k.bytes().all(|b| b.is_ascii_digit()))
+        {
+            return kelvin
+                .parse()
+                .map(Color::Temperature)
+                .map_err(|_| invalid());
+        }
+        if let Some((function, arguments)) = color.split_once('(') {
+            let arguments = arguments.strip_suffix(')').ok_or_else(invalid)?;
+            let arguments: Vec<&str> = arguments
+                .split(|c: char| c == ',' || c == '/' || c.is_whitespace())
+                .filter(|argument| !argument.is_empty())
+                .collect();
+            // an alpha channel is accepted and ignored
+            if arguments.len() != 3 && arguments.len() != 4 {
+                return Err(invalid());
+            }
+            let number = |argument: &str, suffix: &str, scale: f32| {
+                argument
+                    .strip_suffix(suffix)
+                    .unwrap_or(argument)
+                    .parse::<f32>()
+                    .map(|n| n / scale)
+                    .map_err(|_| invalid())
+            };
+            let rgb = match function.trim() {
+                "rgb" | "rgba" => {
+                    let channel = |argument: &str| {
+                        if argument.ends_with('%') {
+                            number(argument, "%", 100.0)
+                        } else {
+                            number(argument, "", 255.0)
+                        }
+                    };
+                    [
+                        channel(arguments[0])?,
+                        channel(arguments[1])?,
+                        channel(arguments[2])?,
+                    ]
+                }
+                "hsl" | "hsla" => css_hsl_to_rgb(
+                    number(arguments[0], "deg", 1.0)?,
+                    number(arguments[1], "%", 100.0)?,
+                    number(arguments[2], "%", 100.0)?,
+                ),
+                _ => return Err(invalid()),
+            };
+            let [red, green, blue] = rgb.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+            return Ok(Color::Rgb(red, green, blue));
+        }
+        let name: String = color.chars().filter(|c| !c.is_whitespace()).collect();
+        CSS_COLORS
+            .iter()
+            .find(|(css, _)| *css == name)
+            .map(|(_, [red, green, blue])| Color::Rgb(*red, *green, *blue))
+            .ok_or_else(invalid)
+    }
+}
+
+impl From<Color> for SendableState {
+    /// Only the color is set, `xy` for colors and `ct` for temperatures clamped to the
+    /// range every white ambiance light supports
+    fn from(color: Color) -> Self {
+        match color {
+            Color::Rgb(red, green, blue) => Self {
+                xy: Some(rgb_to_xy([red, green, blue])),
+                ..Self::default()
+            },
+            Color::Temperature(kelvin) => Self {
+                ct: Some(kelvin_to_mired_for(kelvin, &Capabilities::default())),
+                ..Self::default()
+            },
+        }
+    }
+}
+
+/// CSS `hsl()` in the encoded sRGB space, hue in degrees and the rest in `0.0..=1.0`
+fn css_hsl_to_rgb(hue: f32, sat: f32, lightness: f32) -> [f32; 3] {
+    let (sat, lightness) = (sat.clamp(0.0, 1.0), lightness.clamp(0.0, 1.0));
+    let channel = |n: f32| {
+        let k = (n + hue.rem_euclid(360.0) / 30.0) % 12.0;
+        let a = sat * lightness.min(1.0 - lightness);
+        lightness - a * (k - 3.0).min(9.0 - k).clamp(-1.0, 1.0)
+    };
+    [channel(0.0), channel(8.0), channel(4.0)]
+}
+
+/// The named colors of CSS
+const CSS_COLORS: &[(&str, [u8; 3])] = &[
+    ("aliceblue", [240, 248, 255]),
+    ("antiquewhite", [250, 235, 215]),
+    ("aqua", [0, 255, 255]),
+    ("aquamarine", [127, 255, 212]),
+    ("azure", [240, 255, 255]),
+    ("beige", [245, 245, 220]),
+    ("bisque", [255, 228, 196]),
+    ("black", [0, 0, 0]),
+    ("blanchedalmond", [255, 235, 205]),
+    ("blue", [0, 0, 255]),
+    ("blueviolet", [138, 43, 226]),
+    ("brown", [165, 42, 42]),
+    ("burlywood", [222, 184, 135]),
+    ("cadetblue", [95, 158, 160]),
+    ("chartreuse", [127, 255, 0]),
+    ("chocolate", [210, 105, 30]),
+    ("coral", [255, 127, 80]),
+    ("cornflowerblue", [100, 149, 237]),
+    ("cornsilk", [255, 248, 220]),
+    ("crimson", [220, 20, 60]),
+    ("cyan", [0, 255, 255]),
+    ("darkblue", [0, 0, 139]),
+    ("darkcyan", [0, 139, 139]),
+    ("darkgoldenrod", [184, 134, 11]),
+    ("darkgray", [169, 169, 169]),
+    ("darkgreen", [0, 100, 0]),
+    ("darkgrey", [169, 169, 169]),
+    ("darkkhaki", [189, 183, 107]),
+    ("darkmagenta", [139, 0, 139]),
+    ("darkolivegreen", [85, 107, 47]),
+    ("darkorange", [255, 140, 0]),
+    ("darkorchid", [153, 50, 204]),
+    ("darkred", [139, 0, 0]),
+    ("darksalmon", [233, 150, 122]),
+    ("darkseagreen", [143, 188, 143]),
+    ("darkslateblue", [72, 61, 139]),
+    ("darkslategray", [47, 79, 79]),
+    ("darkslategrey", [47, 79, 79]),
+    ("darkturquoise", [0, 206, 209]),
+    ("darkviolet", [148, 0, 211]),
+    ("deeppink", [255, 20, 147]),
+    ("deepskyblue", [0, 191, 255]),
+    ("dimgray", [105, 105, 105]),
+    ("dimgrey", [105, 105, 105]),
+    ("dodgerblue", [30, 144, 255]),
+    ("firebrick", [178, 34, 34]),
+    ("floralwhite", [255, 250, 240]),
+    ("forestgreen", [34, 139, 34]),
+    ("fuchsia", [255, 0, 255]),
+    ("gainsboro", [220, 220, 220]),
+    ("ghostwhite", [248, 248, 255]),
+    ("gold", [255, 215, 0]),
+    ("goldenrod", [218, 165, 32]),
+    ("gray", [128, 128, 128]),
+    ("green", [0, 128, 0]),
+    ("greenyellow", [173, 255, 47]),
+    ("grey", [128, 128, 128]),
+    ("honeydew", [240, 255, 240]),
+    ("hotpink", [255, 105, 180]),
+    ("indianred", [205, 92, 92]),
+    ("indigo", [75, 0, 130]),
+    ("ivory", [255, 255, 240]),
+    ("khaki", [240, 230, 140]),
+    ("lavender", [230, 230, 250]),
+    ("lavenderblush", [255, 240, 245]),
+    ("lawngreen", [124, 252, 0]),
+    ("lemonchiffon", [255, 250, 205]),
+    ("lightblue", [173, 216, 230]),
+    ("lightcoral", [240, 128, 128]),
+    ("lightcyan", [224, 255, 255]),
+    ("lightgoldenrodyellow", [250, 250, 210]),
+    ("lightgray", [211, 211, 211]),
+    ("lightgreen", [144, 238, 144]),
+    ("lightgrey", [211, 211, 211]),
+    ("lightpink", [255, 182, 193]),
+    ("lightsalmon", [255, 160, 122]),
+    ("lightseagreen", [32, 178, 170]),
+    ("lightskyblue", [135, 206, 250]),
+    ("lightslategray", [119, 136, 153]),
+    ("lightslategrey", [119, 136, 153]),
+    ("lightsteelblue", [176, 196, 222]),
+    ("lightyellow", [255, 255, 224]),
+    ("lime", [0, 255, 0]),
+    ("limegreen", [50, 205, 50]),
+    ("linen", [250, 240, 230]),
+    ("magenta", [255, 0, 255]),
+    ("maroon", [128, 0, 0]),
+    ("mediumaquamarine", [102, 205, 170]),
+    ("mediumblue", [0, 0, 205]),
+    ("mediumorchid", [186, 85, 211]),
+    ("mediumpurple", [147, 112, 219]),
+    ("mediumseagreen", [60, 179, 113]),
+    ("mediumslateblue", [123, 104, 238]),
+    ("mediumspringgreen", [0, 250, 154]),
+    ("mediumturquoise", [72, 209, 204]),
+    ("mediumvioletred", [199, 21, 133]),
+    ("midnightblue", [25, 25, 112]),
+    ("mintcream", [245, 255, 250]),
+    ("mistyrose", [255, 228, 225]),
+    ("moccasin", [255, 228, 181]),
+    ("navajowhite", [255, 222, 173]),
+    ("navy", [0, 0, 128]),
+    ("oldlace", [253, 245, 230]),
+    ("olive", [128, 128, 0]),
+    ("olivedrab", [107, 142, 35]),
+    ("orange", [255, 165, 0]),
+    ("orangered", [255, 69, 0]),
+    ("orchid", [218, 112, 214]),
+    ("palegoldenrod", [238, 232, 170]),
+    ("palegreen", [152, 251, 152]),
+    ("paleturquoise", [175, 238, 238]),
+    ("palevioletred", [219, 112, 147]),
+    ("papayawhip", [255, 239, 213]),
+    ("peachpuff", [255, 218, 185]),
+    ("peru", [205, 133, 63]),
+    ("pink", [255, 192, 203]),
+    ("plum", [221, 160, 221]),
+    ("powderblue", [176, 224, 230]),
+    ("purple", [128, 0, 128]),
+    ("rebeccapurple", [102, 51, 153]),
+    ("red", [255, 0, 0]),
+    ("rosybrown", [188, 143, 143]),
+    ("royalblue", [65, 105, 225]),
+    ("saddlebrown", [139, 69, 19]),
+    ("salmon", [250, 128, 114]),
+    ("sandybrown", [244, 164, 96]),
+    ("seagreen", [46, 139, 87]),
+    ("seashell", [255, 245, 238]),
+    ("sienna", [160, 82, 45]),
+    ("silver", [192, 192, 192]),
+    ("skyblue", [135, 206, 235]),
+    ("slateblue", [106, 90, 205]),
+    ("slategray", [112, 128, 144]),
+    ("slategrey", [112, 128, 144]),
+    ("snow", [255, 250, 250]),
+    ("springgreen", [0, 255, 127]),
+    ("steelblue", [70, 130, 180]),
+    ("tan", [210, 180, 140]),
+    ("teal", [0, 128, 128]),
+    ("thistle", [216, 191, 216]),
+    ("tomato", [255, 99, 71]),
+    ("turquoise", [64, 224, 208]),
+    ("violet", [238, 130, 238]),
+    ("wheat", [245, 222, 179]),
+    ("white", [255, 255, 255]),
+    ("whitesmoke", [245, 245, 245]),
+    ("yellow", [255, 255, 0]),
+    ("yellowgreen", [154, 205, 50]),
+];
+
+/// Perceived lightness (CIE `L*`, `0.0..=100.0`) of a light at a brightness
+pub fn bri_to_lightness(bri: u8) -> f32 {
+    let luminance = f32::from(bri) / 254.0;
+    if luminance > 216.0 / 24389.0 {
+        116.0 * luminance.cbrt() - 16.0
+    } else {
+        luminance * 24389.0 / 27.0
+    }
+}
+
+/// Brightness of a light with a perceived lightness (CIE `L*`, `0.0..=100.0`),
+/// clamped to the `1..=254` the bridge accepts
+pub fn lightness_to_bri(lightness: f32) -> u8 {
+    let lightness = lightness.clamp(0.0, 100.0);
+    let luminance = if lightness > 8.0 {
+        ((lightness + 16.0) / 116.0).powi(3)
+    } else {
+        lightness * 27.0 / 24389.0
+    };
+    (luminance * 254.0).round().clamp(1.0, 254.0) as u8
+}
+
+/// `steps` brightnesses from `from` to `to` included, evenly spaced in perceived
+/// lightness so that fades do not seem to rush through the dark end
+pub fn interpolate_bri(from: u8, to: u8, steps: usize) -> Vec<u8> {
+    let (from, to) = (bri_to_lightness(from), bri_to_lightness(to));
+    factors(steps)
+        .map(|factor| lightness_to_bri(from + (to - from) * factor))
+        .collect()
+}
+
+/// `steps` colors from `from` to `to` included, blended in the CIE Lab space
+pub fn interpolate_lab(from: Color, to: Color, steps: usize) -> Vec<Color> {
+    let (from, to): (Lab, Lab) = (to_linear(from).into(), to_linear(to).into());
+    factors(steps)
+        .map(|factor| from_linear(from.mix(&to, factor).into()))
+        .collect()
+}
+
+/// `steps` colors from `from` to `to` included, blended in the CIE LCh space.
+///
+/// The hue turns the shortest way around, which keeps saturated colors saturated
+/// where Lab would go through grey.
+///
+/// ```
+/// use lighthouse::color::{interpolate_lch, Color};
+/// let colors = interpolate_lch("red".parse().unwrap(), "blue".parse().unwrap(), 5);
+/// assert_eq!(colors.len(), 5);
+/// assert_eq!(colors[0], Color::Rgb(255, 0, 0));
+/// assert_eq!(colors[4], Color::Rgb(0, 0, 255));
+/// ```
+pub fn interpolate_lch(from: Color, to: Color, steps: usize) -> Vec<Color> {
+    let (from, to): (Lch, Lch) = (to_linear(from).into(), to_linear(to).into());
+    factors(steps)
+        .map(|factor| from_linear(from.mix(&to, factor).into()))
+        .collect()
+}
+
+/// `steps` states fading from a color and brightness to another, colors blended in
+/// LCh and brightness spaced perceptually.
+///
+/// Send them one after the other to a light, or spread them over several with
+/// `state_to_multiple` to draw a gradient.
+///
+/// ```
+/// use lighthouse::color::{fade, Color};
+/// let states = fade((Color::Temperature(2700), 50), ("orange".parse().unwrap(), 254), 10);
+/// assert_eq!(states.len(), 10);
+/// assert_eq!(states[9].bri, Some(254));
+/// ```
+pub fn fade(from: (Color, u8), to: (Color, u8), steps: usize) -> Vec<SendableState> {
+    interpolate_lch(from.0, to.0, steps)
+        .into_iter()
+        .zip(interpolate_bri(from.1, to.1, steps))
+        .map(|(color, bri)| SendableState {
+            bri: Some(bri),
+            xy: Some(rgb_to_xy(color.to_rgb())),
+            ..SendableState::default()
+        })
         .collect()
 }
+
+/// Mixing factors of `steps` evenly spaced points, both ends included
+fn factors(steps: usize) -> impl Iterator<Item = f32> {
+    let last = steps.saturating_sub(1).max(1) as f32;
+    (0..steps).map(move |step| step as f32 / last)
+}
+
+fn to_linear(color: Color) -> LinSrgb {
+    let [red, green, blue] = color.to_rgb();
+    Srgb::new(red, green, blue)
+        .into_format::<f32>()
+        .into_linear()
+}
+
+fn from_linear(rgb: LinSrgb) -> Color {
+    let (red, green, blue) = Srgb::from_linear(rgb.clamp())
+        .into_format::<u8>()
+        .into_components();
+    Color::Rgb(red, green, blue)
+}
@@ -0,0 +1,170 @@
+/// # Config module
+///
+/// This module contains the representations of the configuration of the bridge
+/// itself: its identity, network and software details, and the whitelist of the
+/// applications allowed to talk to it.
+///
+/// A subset of the configuration is available without a token, which is useful to
+/// identify a bridge before registering with it.
+// imports
+use crate::helpers::parse_timestamp;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, time::Duration};
+
+/// Configuration of the bridge as returned to an authorized token
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BridgeConfig {
+    pub name: String,
+    pub bridgeid: String,
+    pub mac: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub modelid: Option<String>,
+    pub apiversion: String,
+    pub swversion: String,
+    pub zigbeechannel: u8,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ipaddress: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dhcp: Option<bool>,
+    /// Timezone of the bridge in the tz database format (e.g. `Europe/London`)
+    pub timezone: String,
+    /// Current local time of the bridge (`2020-07-03T12:34:56`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub localtime: Option<String>,
+    /// Current UTC time of the bridge
+    #[serde(rename = "UTC", skip_serializing_if = "Option::is_none")]
+    pub utc: Option<String>,
+    /// Applications allowed to use the API, mapped by their token
+    #[serde(default)]
+    pub whitelist: BTreeMap<String, WhitelistEntry>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub portalstate: Option<PortalState>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub swupdate2: Option<SoftwareUpdate>,
+    /// True for 30 seconds after the link button was pressed
+    pub linkbutton: bool,
+}
+
+impl std::fmt::Display for BridgeConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{{ bridgeid: {}, apiversion: {} }} : {}",
+            self.bridgeid, self.apiversion, self.name
+        )
+    }
+}
+
+/// An application allowed to use the API
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct WhitelistEntry {
+    /// Device type given at registration (e.g. `lighthouse`)
+    pub name: String,
+    /// UTC time the token was created
+    #[serde(rename = "create date")]
+    pub created: String,
+    /// UTC time the token was last used
+    #[serde(rename = "last use date")]
+    pub last_used: String,
+}
+
+impl WhitelistEntry {
+    /// How long the token has not been used, given the current UTC time of the bridge.
+    ///
+    /// `None` if either date is not in the `2020-07-03T12:34:56` format.
+    ///
+    /// ```
+    /// use lighthouse::config::WhitelistEntry;
+    /// let entry = WhitelistEntry {
+    ///     name: String::from("lighthouse"),
+    ///     created: String::from("2020-02-01T10:00:00"),
+    ///     last_used: String::from("2020-02-28T10:00:00"),
+    /// };
+    /// let idle = entry.idle_time("2020-03-01T10:00:00").unwrap();
+    /// assert_eq!(idle.as_secs(), 2 * 24 * 60 * 60);
+    /// ```
+    pub fn idle_time(&self, now: &str) -> Option<Duration> {
+        let idle = parse_timestamp(now)?.checked_sub(parse_timestamp(&self.last_used)?)?;
+        Some(Duration::from_secs(idle.max(0) as u64))
+    }
+}
+
+/// Connection state of the bridge to the Hue portal
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PortalState {
+    pub signedon: bool,
+    pub incoming: bool,
+    pub outgoing: bool,
+    pub communication: String,
+}
+
+/// Software update state of the bridge and the devices connected to it
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SoftwareUpdate {
+    /// One of `unknown`, `noupdates`, `transferring`, `anyreadytoinstall` or `allreadytoinstall`
+    pub state: String,
+    #[serde(default)]
+    pub checkforupdate: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lastchange: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub autoinstall: Option<AutoInstall>,
+}
+
+/// Automatic installation of software updates
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AutoInstall {
+    pub on: bool,
+    /// Recurring time pattern of the installation window (`W127/T02:00:00`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updatetime: Option<String>,
+}
+
+/// Changes to the writable part of the configuration, only the fields that are
+/// `Some` are sent.
+///
+/// ```
+/// use lighthouse::config::ConfigUpdate;
+/// let update = ConfigUpdate {
+///     name: Some(String::from("Upstairs")),
+///     timezone: Some(String::from("Europe/London")),
+///     ..ConfigUpdate::default()
+/// };
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct ConfigUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+    /// One of the Zigbee channels 11, 15, 20 or 25
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zigbeechannel: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dhcp: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ipaddress: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub netmask: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gateway: Option<String>,
+}
+
+/// Configuration of the bridge available without a token
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PublicConfig {
+    pub name: String,
+    pub bridgeid: String,
+    pub mac: String,
+    pub modelid: String,
+    pub apiversion: String,
+    pub swversion: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub datastoreversion: Option<String>,
+    /// True until the bridge has been set up by an application
+    #[serde(default)]
+    pub factorynew: bool,
+    /// Id of the bridge this one replaced, when restored from a backup
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replacesbridgeid: Option<String>,
+}
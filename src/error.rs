@@ -0,0 +1,107 @@
+/// # Error module
+///
+/// This module contains the error type returned by the fallible parts of the
+/// library. Nothing in the public API should panic because the bridge is offline
+/// or a light is unplugged, instead one of these variants is returned.
+// imports
+use crate::response::{ApiError, ErrorType};
+use std::fmt;
+
+/// Convenience alias for results produced by this crate
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// All the ways talking to the bridge (or preparing to) can fail
+#[derive(Debug)]
+pub enum Error {
+    /// The request could not be sent or the response could not be read
+    Transport(reqwest::Error),
+    /// The bridge answered with a non-success HTTP status code
+    HttpStatus(reqwest::StatusCode),
+    /// The bridge answered, but the Hue API reported an error
+    Api(ApiError),
+    /// The response body did not match the expected structure
+    Deserialization(serde_json::Error),
+    /// Reading or writing a persisted bridge failed
+    Persistence(String),
+    /// Finding a bridge on the network failed
+    Discovery(String),
+    /// The bridge target URL could not be constructed
+    InvalidTarget(String),
+    /// A color value could not be parsed or converted
+    InvalidColor(String),
+    /// A light state holds values the bridge would reject
+    InvalidState(String),
+    /// A schedule time pattern could not be parsed
+    InvalidTimePattern(String),
+    /// The tokio runtime driving the blocking API could not be created
+    Runtime(String),
+    /// The bridge did not finish an operation in the given time
+    Timeout(String),
+}
+
+impl Error {
+    /// Builds an `Error::Api` from the `error` object of a Hue response.
+    ///
+    /// An object without a valid `type` code is reported as `Error::Deserialization`.
+    pub(crate) fn from_api_value(error: &serde_json::Value) -> Self {
+        match serde_json::from_value::<ApiError>(error.clone()) {
+            Ok(error) => Self::Api(error),
+            Err(err) => Self::Deserialization(err),
+        }
+    }
+
+    /// The Hue error type, if this is an error reported by the API
+    pub fn api_error_type(&self) -> Option<ErrorType> {
+        match self {
+            Self::Api(error) => Some(error.kind),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Transport(err) => write!(f, "transport error: {}", err),
+            Self::HttpStatus(status) => write!(f, "bridge responded with HTTP status {}", status),
+            Self::Api(error) => write!(f, "{}", error),
+            Self::Deserialization(err) => write!(f, "could not decode response: {}", err),
+            Self::Persistence(msg) => write!(f, "persistence error: {}", msg),
+            Self::Discovery(msg) => write!(f, "discovery error: {}", msg),
+            Self::InvalidTarget(msg) => write!(f, "invalid bridge target: {}", msg),
+            Self::InvalidColor(msg) => write!(f, "invalid color: {}", msg),
+            Self::InvalidState(msg) => write!(f, "invalid light state: {}", msg),
+            Self::InvalidTimePattern(msg) => write!(f, "invalid time pattern: {}", msg),
+            Self::Runtime(msg) => write!(f, "could not create runtime: {}", msg),
+            Self::Timeout(msg) => write!(f, "timed out: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Transport(err) => Some(err),
+            Self::Deserialization(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(err: reqwest::Error) -> Self {
+        Self::Transport(err)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Deserialization(err)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Self::Persistence(err.to_string())
+    }
+}
@@ -0,0 +1,120 @@
+/// # Groups module
+///
+/// This module contains the representations of the groups (rooms, zones and
+/// plain light groups) stored on the bridge.
+///
+/// Sending a state to a group is a single request to the bridge, which is much
+/// kinder to the Zigbee network than sending the same state to each light.
+// imports
+use crate::lights::SendableState;
+use serde::{Deserialize, Serialize};
+
+/// The kind of a group
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupType {
+    /// A plain collection of lights
+    LightGroup,
+    /// A room, every light can only be in a single room
+    Room,
+    /// A zone, lights can be in several zones
+    Zone,
+    /// A group used for entertainment streaming
+    Entertainment,
+    /// Multisource luminaire, created by the bridge
+    Luminaire,
+    /// Part of a multisource luminaire, created by the bridge
+    #[serde(rename = "Lightsource")]
+    LightSource,
+    /// Any group type not known by this crate
+    #[serde(other)]
+    Other,
+}
+
+/// Summary of the state of the lights in a group
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct GroupState {
+    pub all_on: bool,
+    pub any_on: bool,
+}
+
+/// A group as returned by the bridge
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Group {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: GroupType,
+    /// Category of a room or zone (e.g. `Living room`, `Kitchen`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub class: Option<String>,
+    #[serde(with = "crate::helpers::string_ids")]
+    pub lights: Vec<u8>,
+    #[serde(default, with = "crate::helpers::string_ids")]
+    pub sensors: Vec<u8>,
+    /// The last state sent to the group
+    #[serde(default)]
+    pub action: SendableState,
+    #[serde(default)]
+    pub state: GroupState,
+    #[serde(default)]
+    pub recycle: bool,
+}
+
+impl std::fmt::Display for Group {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{{ any_on: {}, all_on: {} }} : {}",
+            self.state.any_on, self.state.all_on, self.name
+        )
+    }
+}
+
+/// The body used to create a new group on the bridge
+///
+/// ```
+/// use lighthouse::groups::*;
+/// let group = NewGroup::room("Kitchen", vec![1, 2]).with_class("Kitchen");
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct NewGroup {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: GroupType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub class: Option<String>,
+    #[serde(with = "crate::helpers::string_ids")]
+    pub lights: Vec<u8>,
+}
+
+impl NewGroup {
+    /// A group of a given type with a name and its lights
+    pub fn new(name: impl Into<String>, kind: GroupType, lights: Vec<u8>) -> Self {
+        Self {
+            name: name.into(),
+            kind,
+            class: None,
+            lights,
+        }
+    }
+
+    /// A plain light group
+    pub fn light_group(name: impl Into<String>, lights: Vec<u8>) -> Self {
+        Self::new(name, GroupType::LightGroup, lights)
+    }
+
+    /// A room, the bridge defaults its class to `Other`
+    pub fn room(name: impl Into<String>, lights: Vec<u8>) -> Self {
+        Self::new(name, GroupType::Room, lights)
+    }
+
+    /// A zone, the bridge defaults its class to `Other`
+    pub fn zone(name: impl Into<String>, lights: Vec<u8>) -> Self {
+        Self::new(name, GroupType::Zone, lights)
+    }
+
+    /// Sets the class of a room or zone
+    pub fn with_class(mut self, class: impl Into<String>) -> Self {
+        self.class = Some(class.into());
+        self
+    }
+}
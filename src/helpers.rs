@@ -3,22 +3,94 @@
 /// The helpers module contains functions that assist the rest of the codebase
 /// it is unlikely that any of these internals will have to be used manually. pub mod helpers {// imports use std::net::IpAddr; use url::Url; /// Generates the target URL for the bridge pub fn generate_target(address: IpAddr, token: &str) -> Result<Url, ()> {let mut target = Url::parse("http://localhost").unwrap(); // Unwrap as it can't fail in parsing let path = format!("api/{}/", token); target.set_path(&path[..]); if target.set_ip_host(address).is_ok() {return Ok(target);} Err(())} pub mod network {use crate::lights::SendableState; use url::Url; /// Defines the allowed methods to be sent to bridge pub enum AllowedMethod {GET, PUT, POST,} /// Implementated to allow controlled conversion into reqwest /// methods and not allow undefined methods to be sent to bridge impl std::convert::From<AllowedMethod> for reqwest::Method {fn from(value: AllowedMethod) -> Self {match value {AllowedMethod::GET => reqwest::Method::GET, AllowedMethod::POST => reqwest::Method::POST, AllowedMethod::PUT => reqwest::Method::PUT,}}} /// This type alias is a URL and a type of Request to be sent pub type RequestTarget = (Url, AllowedMethod); /// Convenience type alias for a possible Result from the reqwest client type ResponseResult = Result<reqwest::Response, reqwest::Error>; type IndexedResponseResult = (usize, ResponseResult); /// Function wrapping the request sending functionality /// to a location. pub async fn send_request(request_target: RequestTarget, state: Option<&SendableState>, client: &reqwest::Client,) -> ResponseResult {let (target, method) = request_target; match method {AllowedMethod::POST => client.post(target).json(&state).send().await, AllowedMethod::GET => client.get(target).send().await, AllowedMethod::PUT => client.put(target).json(&state).send().await,}} pub async fn send_request_indexed(index: usize, request_target: RequestTarget, state: Option<&SendableState>, client: &reqwest::Client,) -> IndexedResponseResult {(index, send_request(request_target, state, client).await)} /// Function that sends off several states to the lights /// This is much more key than individual requests functionality provided by the /// send_request function as this is allowing us to do this asynchronously across /// an arbitrary selection of lights. pub async fn send_requests(request_targets: impl IntoIterator<Item = RequestTarget>, states: impl IntoIterator<Item = Option<&SendableState>>, client: &reqwest::Client,) -> Vec<ResponseResult> {use tokio::stream::StreamExt; let mut f: futures::stream::FuturesUnordered<_> = request_targets .into_iter() .zip(states.into_iter()) .enumerate() .map(|(i, (target, state))| send_request_indexed(i, target, state, client)) .collect(); let mut res = Vec::with_capacity(f.len()); while let Some(tup) = f.next().await {res.push(tup);} res.sort_by_key(|tuple| tuple.0); res.into_iter().map(|tup| tup.1).collect()}}}
 // imports
+use crate::error::{Error, Result};
 use std::net::IpAddr;
 use url::Url;
 
 /// Generates the target URL for the bridge
-pub fn generate_target(address: IpAddr, token: &str) -> Result<Url, ()> {
+pub fn generate_target(address: IpAddr, token: &str) -> Result<Url> {
     let mut target = Url::parse("http://localhost").unwrap(); // Unwrap as it can't fail in parsing
     let path = format!("api/{}/", token);
     target.set_path(&path[..]);
-    if target.set_ip_host(address).is_ok() {
-        return Ok(target);
+    target
+        .set_ip_host(address)
+        .map_err(|_| Error::InvalidTarget(format!("could not use {} as the host", address)))?;
+    Ok(target)
+}
+
+/// Generates the target URL for a bridge reachable on any host, port and scheme.
+///
+/// The host can be an IP address (IPv6 in brackets) or a hostname.
+pub fn generate_url(scheme: &str, host: &str, port: Option<u16>, token: &str) -> Result<Url> {
+    let mut target = Url::parse(&format!("{}://{}", scheme, host))
+        .map_err(|e| Error::InvalidTarget(format!("`{}://{}`: {}", scheme, host, e)))?;
+    target
+        .set_port(port)
+        .map_err(|_| Error::InvalidTarget(format!("could not set the port of `{}`", host)))?;
+    target.set_path(&format!("api/{}/", token)[..]);
+    Ok(target)
+}
+
+/// Year, month and day of a `2020-07-03` date
+pub(crate) fn parse_date(s: &str) -> Option<(u16, u8, u8)> {
+    let [year, month, day] = split_numbers(s, '-')?;
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || year > u32::from(u16::MAX) {
+        return None;
+    }
+    Some((year as u16, month as u8, day as u8))
+}
+
+/// Hour, minute and second of a `12:34:56` time
+pub(crate) fn parse_time(s: &str) -> Option<(u8, u8, u8)> {
+    let [hour, minute, second] = split_numbers(s, ':')?;
+    if hour > 23 || minute > 59 || second > 59 {
+        return None;
+    }
+    Some((hour as u8, minute as u8, second as u8))
+}
+
+/// Seconds since the epoch of a `2020-07-03T12:34:56` UTC date and time
+pub(crate) fn parse_timestamp(s: &str) -> Option<i64> {
+    let (date, time) = s.split_once('T')?;
+    let (year, month, day) = parse_date(date)?;
+    let (hour, minute, second) = parse_time(time)?;
+
+    // days since the epoch of a date of the proleptic Gregorian calendar
+    let (month, day) = (i64::from(month), i64::from(day));
+    let year = i64::from(year) - if month <= 2 { 1 } else { 0 };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+
+    Some(days * 86_400 + i64::from(hour) * 3_600 + i64::from(minute) * 60 + i64::from(second))
+}
+
+/// Exactly three numbers separated by `separator`
+fn split_numbers(s: &str, separator: char) -> Option<[u32; 3]> {
+    let mut numbers = [0; 3];
+    let mut parts = s.split(separator);
+    for number in numbers.iter_mut() {
+        let part = parts.next()?;
+        if part.is_empty() || !part.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        *number = part.parse().ok()?;
+    }
+    match parts.next() {
+        Some(_) => None,
+        None => Some(numbers),
     }
-    Err(())
 }
 
 pub mod network {
-    use crate::lights::SendableState;
+    use crate::{
+        error::{Error, Result},
+        lights::SendableState,
+        response::ApiResponse,
+    };
+    use serde::{de::DeserializeOwned, Serialize};
     use url::Url;
 
     /// Defines the allowed methods to be sent to bridge
@@ -26,6 +98,7 @@ pub mod network {
         GET,
         PUT,
         POST,
+        DELETE,
     }
 
     /// Implementated to allow controlled conversion into reqwest
@@ -36,6 +109,7 @@ pub mod network {
                 AllowedMethod::GET => reqwest::Method::GET,
                 AllowedMethod::POST => reqwest::Method::POST,
                 AllowedMethod::PUT => reqwest::Method::PUT,
+                AllowedMethod::DELETE => reqwest::Method::DELETE,
             }
         }
     }
@@ -44,24 +118,77 @@ pub mod network {
     pub type RequestTarget = (Url, AllowedMethod);
 
     /// Convenience type alias for a possible Result from the reqwest client
-    type ResponseResult = Result<reqwest::Response, reqwest::Error>;
+    type ResponseResult = Result<reqwest::Response>;
     type IndexedResponseResult = (usize, ResponseResult);
 
     /// Function wrapping the request sending functionality
     /// to a location.
+    ///
+    /// Responses with a non-success HTTP status are turned into `Error::HttpStatus`.
     pub async fn send_request(
         request_target: RequestTarget,
         state: Option<&SendableState>,
         client: &reqwest::Client,
+    ) -> ResponseResult {
+        send_body(request_target, state, client).await
+    }
+
+    /// Same as `send_request` but with any serializable body, used for the
+    /// endpoints that do not take a light state (groups, scenes, config, ...).
+    pub async fn send_body<B: Serialize + ?Sized>(
+        request_target: RequestTarget,
+        body: Option<&B>,
+        client: &reqwest::Client,
     ) -> ResponseResult {
         let (target, method) = request_target;
-        match method {
-            AllowedMethod::POST => client.post(target).json(&state).send().await,
+        let response = match method {
+            AllowedMethod::POST => client.post(target).json(&body).send().await,
             AllowedMethod::GET => client.get(target).send().await,
-            AllowedMethod::PUT => client.put(target).json(&state).send().await,
+            AllowedMethod::PUT => client.put(target).json(&body).send().await,
+            AllowedMethod::DELETE => client.delete(target).send().await,
+        }?;
+        if response.status().is_success() {
+            Ok(response)
+        } else {
+            Err(Error::HttpStatus(response.status()))
         }
     }
 
+    /// Decodes the body of a response from the bridge into the requested type.
+    ///
+    /// The bridge reports failures as a `200 OK` with an array of error objects,
+    /// so the body is checked for those first and the first one found is returned
+    /// as `Error::Api`.
+    pub async fn parse_response<T: DeserializeOwned>(response: reqwest::Response) -> Result<T> {
+        let body: serde_json::Value = response.json().await?;
+        if let Some(error) = body
+            .as_array()
+            .and_then(|items| items.iter().find_map(|item| item.get("error")))
+        {
+            return Err(Error::from_api_value(error));
+        }
+        Ok(serde_json::from_value(body)?)
+    }
+
+    /// Decodes the success/error array the bridge sends back when modifying
+    /// a resource. Errors reported by the API are kept inside the `ApiResponse`.
+    pub async fn parse_api_response(response: reqwest::Response) -> Result<ApiResponse> {
+        Ok(response.json().await?)
+    }
+
+    /// Decodes the responses returned by `send_requests`, keeping their order.
+    /// Fails on the first request that could not be sent or decoded.
+    pub async fn parse_api_responses(
+        responses: impl IntoIterator<Item = ResponseResult>,
+    ) -> Result<Vec<ApiResponse>> {
+        futures::future::try_join_all(
+            responses
+                .into_iter()
+                .map(|response| async { parse_api_response(response?).await }),
+        )
+        .await
+    }
+
     pub async fn send_request_indexed(
         index: usize,
         request_target: RequestTarget,
@@ -83,7 +210,7 @@ pub mod network {
         use tokio::stream::StreamExt;
         let mut f: futures::stream::FuturesUnordered<_> = request_targets
             .into_iter()
-            .zip(states.into_iter())
+            .zip(states)
             .enumerate()
             .map(|(i, (target, state))| send_request_indexed(i, target, state, client))
             .collect();
@@ -95,3 +222,42 @@ pub mod network {
         res.into_iter().map(|tup| tup.1).collect()
     }
 }
+
+/// Serde helpers for the lists of ids the API sends as strings (`["1", "2"]`)
+/// while the rest of the crate uses numeric ids.
+///
+/// Use with `#[serde(with = "crate::helpers::string_ids")]`.
+pub mod string_ids {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(ids: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(ids.iter().map(|id| id.to_string()))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        Vec::<String>::deserialize(deserializer)?
+            .into_iter()
+            .map(|id| id.parse().map_err(D::Error::custom))
+            .collect()
+    }
+}
+
+/// Same as `string_ids` but for a single optional id (`"1"`)
+///
+/// Use with `#[serde(default, with = "crate::helpers::optional_string_id")]`.
+pub mod optional_string_id {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(id: &Option<u8>, serializer: S) -> Result<S::Ok, S::Error> {
+        match id {
+            Some(id) => serializer.serialize_str(&id.to_string()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u8>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|id| id.parse().map_err(D::Error::custom))
+            .transpose()
+    }
+}
@@ -14,6 +14,20 @@
 //! let lights = bridge.get_lights();
 //! ```
 //!
+//! For more control (hostnames, ports, timeouts, token validation) use the `BridgeBuilder`:
+//!
+//! ```no_run
+//! use std::time::Duration;
+//! use lighthouse::bridge::Bridge;
+//! let bridge = Bridge::builder()
+//!     .host("philips-hue.local")
+//!     .token("my-example-token")
+//!     .timeout(Duration::from_secs(2))
+//!     .validate()
+//!     .build()
+//!     .unwrap();
+//! ```
+//!
 //! ## Controlling individual lights
 //!
 //! ```no_run
@@ -22,12 +36,36 @@
 //! let mut bridge = Bridge::new("192.168.1.10".parse().unwrap(), "token".to_string()).unwrap();
 //! bridge.state_to(1, state!(on: true, bri: 128));
 //! ```
+//!
+//! ## Using the bridge from async code
+//!
+//! The `Bridge` owns a runtime and blocks on it. Inside of an existing tokio application use the
+//! `AsyncBridge` instead, which exposes the same methods as `async fn`s.
+//!
+//! ```no_run
+//! # async fn run() -> Result<(), lighthouse::Error> {
+//! use lighthouse::{bridge::AsyncBridge, state};
+//! let bridge = AsyncBridge::new("192.168.1.10".parse().unwrap(), "token".to_string())?;
+//! bridge.state_to(1, state!(on: true, bri: 128)).await?;
+//! # Ok(())
+//! # }
+//! ```
 
-// TODO: Implement a Bridge Builder and move the building functions out of the actual bridge
-// TODO: Add validation check for when making a bridge - ping some API endpoint to collect data. Good way to get more info as well about the bridge
-
+#[cfg(feature = "color")]
+pub mod adapt;
 pub mod bridge;
 #[cfg(feature = "color")]
 pub mod color;
+pub mod config;
+pub mod error;
+pub mod groups;
 pub mod helpers;
 pub mod lights;
+pub mod response;
+pub mod rules;
+pub mod scenes;
+pub mod schedules;
+pub mod search;
+pub mod sensors;
+
+pub use error::Error;
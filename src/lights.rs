@@ -3,8 +3,9 @@
 /// This module contains the core representations for the lights and responses
 /// from the API.
 // imports
+use crate::error::{Error, Result};
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use std::convert::TryFrom;
 
 /// Struct that can be sent to the Hue lights. It mirrors closely the
 /// `State`.
@@ -15,6 +16,9 @@ use serde_json::Value;
 /// let state_2: SendableState = SendableState {on:Some(true), ..SendableState::default()};
 /// let state_3: &SendableState = state!(on: true, xy: [1.0, 0.123]);
 /// let state_4: SendableState = state!(nonref; on: true, xy: [1.0, 0.123]);
+/// // dims by 10% of the full range over two seconds
+/// let state_5: &SendableState =
+///     state!(bri_inc: -25, transitiontime: std::time::Duration::from_secs(2));
 /// ```
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SendableState {
@@ -27,15 +31,313 @@ pub struct SendableState {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub sat: Option<u8>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub effect: Option<String>,
+    pub effect: Option<Effect>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub xy: Option<[f32; 2]>,
+    /// Color temperature in mireds
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub alert: Option<String>,
+    pub ct: Option<u16>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub transitiontime: Option<u8>,
+    pub alert: Option<Alert>,
+    /// Duration of the transition in multiples of 100ms
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub colormode: Option<String>,
+    pub transitiontime: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub colormode: Option<ColorMode>,
+    /// Relative change of `bri`, ignored if `bri` is set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bri_inc: Option<i16>,
+    /// Relative change of `sat`, ignored if `sat` is set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sat_inc: Option<i16>,
+    /// Relative change of `hue`, wrapping around, ignored if `hue` is set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hue_inc: Option<i32>,
+    /// Relative change of `ct`, ignored if `ct` is set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ct_inc: Option<i32>,
+    /// Relative change of `xy`, ignored if `xy` is set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub xy_inc: Option<[f32; 2]>,
+}
+
+impl SendableState {
+    /// Creates a `StateBuilder` taking values in everyday units
+    pub fn builder() -> StateBuilder {
+        StateBuilder::default()
+    }
+
+    /// Checks that the values are within the ranges accepted by the bridge, so that
+    /// mistakes show up before anything is sent.
+    ///
+    /// ```
+    /// use lighthouse::{lights::*, state};
+    /// assert!(state!(bri: 254, ct: 366).validate().is_ok());
+    /// assert!(state!(bri: 255).validate().is_err());
+    /// assert!(state!(xy: [1.2, 0.3]).validate().is_err());
+    /// ```
+    pub fn validate(&self) -> Result<()> {
+        check("bri", self.bri, |bri| (1..=254).contains(&bri))?;
+        check("sat", self.sat, |sat| sat <= 254)?;
+        check("ct", self.ct, |ct| (153..=500).contains(&ct))?;
+        check("xy", self.xy, |xy| {
+            xy.iter().all(|c| (0.0..=1.0).contains(c))
+        })?;
+        check("bri_inc", self.bri_inc, |inc| (-254..=254).contains(&inc))?;
+        check("sat_inc", self.sat_inc, |inc| (-254..=254).contains(&inc))?;
+        check("hue_inc", self.hue_inc, |inc| {
+            (-65534..=65534).contains(&inc)
+        })?;
+        check("ct_inc", self.ct_inc, |inc| (-65534..=65534).contains(&inc))?;
+        check("xy_inc", self.xy_inc, |xy| {
+            xy.iter().all(|c| (-0.5..=0.5).contains(c))
+        })?;
+        check("alert", self.alert, |alert| alert != Alert::Unknown)?;
+        check("effect", self.effect, |effect| effect != Effect::Unknown)?;
+        check("colormode", self.colormode, |mode| {
+            mode != ColorMode::Unknown
+        })?;
+        Ok(())
+    }
+
+    /// Validating constructor, returns the state if `validate` accepts it
+    ///
+    /// ```
+    /// use lighthouse::lights::*;
+    /// let state = SendableState::checked(SendableState {
+    ///     on: Some(true),
+    ///     bri: Some(0),
+    ///     ..SendableState::default()
+    /// });
+    /// assert!(state.is_err());
+    /// ```
+    pub fn checked(state: Self) -> Result<Self> {
+        state.validate()?;
+        Ok(state)
+    }
+}
+
+/// Fluent builder for `SendableState`, converting everyday units into the ones
+/// of the API.
+///
+/// ```
+/// use lighthouse::lights::*;
+/// use std::time::Duration;
+/// let state = SendableState::builder()
+///     .on()
+///     .brightness_percent(40.0)
+///     .mired(370)
+///     .transition(Duration::from_secs(2))
+///     .build()
+///     .unwrap();
+/// assert_eq!(state.bri, Some(102));
+/// assert_eq!(state.ct, Some(370));
+/// assert_eq!(state.transitiontime, Some(20));
+/// ```
+///
+/// Values the bridge would reject, and colors that cannot be parsed, are reported
+/// by `build`.
+#[derive(Debug, Default)]
+pub struct StateBuilder {
+    state: SendableState,
+    error: Option<Error>,
+}
+
+impl StateBuilder {
+    /// Turns the light on
+    pub fn on(mut self) -> Self {
+        self.state.on = Some(true);
+        self
+    }
+
+    /// Turns the light off
+    pub fn off(mut self) -> Self {
+        self.state.on = Some(false);
+        self
+    }
+
+    /// Brightness in API units, 1 to 254
+    pub fn bri(mut self, bri: u8) -> Self {
+        self.state.bri = Some(bri);
+        self
+    }
+
+    /// Brightness from 0 to 100%, 0% being the dimmest the light can go
+    pub fn brightness_percent(mut self, percent: f32) -> Self {
+        let percent = percent.clamp(0.0, 100.0);
+        self.state.bri = Some((1.0 + percent / 100.0 * 253.0).round() as u8);
+        self
+    }
+
+    /// Relative brightness change in API units, -254 to 254
+    pub fn bri_inc(mut self, inc: i16) -> Self {
+        self.state.bri_inc = Some(inc);
+        self
+    }
+
+    /// Hue in API units, 0 to 65535 going around the color wheel
+    pub fn hue(mut self, hue: u16) -> Self {
+        self.state.hue = Some(hue);
+        self
+    }
+
+    /// Hue in degrees around the color wheel, wrapping around
+    pub fn hue_degrees(mut self, degrees: f32) -> Self {
+        let turn = degrees.rem_euclid(360.0) / 360.0;
+        self.state.hue = Some((turn * 65535.0).round() as u16);
+        self
+    }
+
+    /// Saturation in API units, 0 to 254
+    pub fn sat(mut self, sat: u8) -> Self {
+        self.state.sat = Some(sat);
+        self
+    }
+
+    /// Saturation from 0 to 100%
+    pub fn saturation_percent(mut self, percent: f32) -> Self {
+        let percent = percent.clamp(0.0, 100.0);
+        self.state.sat = Some((percent / 100.0 * 254.0).round() as u8);
+        self
+    }
+
+    /// Color as CIE xy coordinates
+    pub fn xy(mut self, x: f32, y: f32) -> Self {
+        self.state.xy = Some([x, y]);
+        self
+    }
+
+    /// Color temperature in mireds, 153 to 500
+    pub fn mired(mut self, mired: u16) -> Self {
+        self.state.ct = Some(mired);
+        self
+    }
+
+    /// Color temperature in Kelvin, clamped to the 2000K to 6500K the bridge accepts
+    #[cfg(feature = "color")]
+    pub fn kelvin(mut self, kelvin: u32) -> Self {
+        self.state.ct = Some(crate::color::kelvin_to_mired_for(
+            kelvin,
+            &Capabilities::default(),
+        ));
+        self
+    }
+
+    /// Color from red, green and blue channels, sent as `xy`.
+    ///
+    /// Only the hue and saturation of the color are used, set the brightness separately.
+    #[cfg(feature = "color")]
+    pub fn rgb(mut self, red: u8, green: u8, blue: u8) -> Self {
+        self.state.xy = Some(crate::color::rgb_to_xy([red, green, blue]));
+        self
+    }
+
+    /// Color from a hex string (`#ff8800`), sent as `xy`.
+    ///
+    /// Only the hue and saturation of the color are used, set the brightness separately.
+    #[cfg(feature = "color")]
+    pub fn hex(self, hex: &str) -> Self {
+        match crate::color::hex_to_rgb(hex) {
+            Ok([red, green, blue]) => self.rgb(red, green, blue),
+            Err(error) => Self {
+                error: Some(error),
+                ..self
+            },
+        }
+    }
+
+    /// Color parsed from user input, see [`Color`](crate::color::Color)
+    #[cfg(feature = "color")]
+    pub fn color(mut self, color: crate::color::Color) -> Self {
+        let state = SendableState::from(color);
+        self.state.xy = state.xy;
+        self.state.ct = state.ct;
+        self
+    }
+
+    /// Duration of the transition, rounded to 100ms
+    pub fn transition(mut self, duration: std::time::Duration) -> Self {
+        self.state.transitiontime = Some(IntoStateField::into_state_field(duration));
+        self
+    }
+
+    /// Makes the light breathe once or for 15 seconds, see [`Alert`]
+    pub fn alert(mut self, alert: Alert) -> Self {
+        self.state.alert = Some(alert);
+        self
+    }
+
+    /// Starts or stops a dynamic effect, see [`Effect`]
+    pub fn effect(mut self, effect: Effect) -> Self {
+        self.state.effect = Some(effect);
+        self
+    }
+
+    /// Returns the state, or the first error met while building it or validating it
+    pub fn build(self) -> Result<SendableState> {
+        match self.error {
+            Some(error) => Err(error),
+            None => SendableState::checked(self.state),
+        }
+    }
+}
+
+/// Fails with `Error::InvalidState` if a value is set and not accepted by `valid`
+fn check<T: Copy + std::fmt::Debug>(
+    field: &str,
+    value: Option<T>,
+    valid: impl Fn(T) -> bool,
+) -> Result<()> {
+    match value {
+        Some(value) if !valid(value) => Err(Error::InvalidState(format!(
+            "`{}` is out of range: {:?}",
+            field, value
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Temporary change of a light used to identify it
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Alert {
+    /// No alert, or stops a running one
+    #[default]
+    None,
+    /// A single breathe cycle
+    Select,
+    /// Breathe cycles for 15 seconds
+    LSelect,
+    /// Any value not known by this crate, rejected by `SendableState::validate`
+    #[serde(other)]
+    Unknown,
+}
+
+/// Dynamic effect of a light
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Effect {
+    None,
+    /// Cycles through all hues with the current brightness and saturation
+    ColorLoop,
+    /// Any value not known by this crate, rejected by `SendableState::validate`
+    #[serde(other)]
+    Unknown,
+}
+
+/// Which of the color attributes the light is currently using
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorMode {
+    /// `hue` and `sat`
+    Hs,
+    /// `xy`
+    Xy,
+    /// `ct`
+    Ct,
+    /// Any value not known by this crate, rejected by `SendableState::validate`
+    #[serde(other)]
+    Unknown,
 }
 
 /// This would be good to reimplement in some way to respect the current state of a given light.
@@ -50,9 +352,15 @@ impl Default for SendableState {
             sat: None,
             effect: None,
             xy: None,
+            ct: None,
             alert: None,
             transitiontime: Some(1),
             colormode: None,
+            bri_inc: None,
+            sat_inc: None,
+            hue_inc: None,
+            ct_inc: None,
+            xy_inc: None,
         }
     }
 }
@@ -68,15 +376,18 @@ pub struct State {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub sat: Option<u8>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub effect: Option<String>,
+    pub effect: Option<Effect>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub xy: Option<[f32; 2]>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ct: Option<u32>,
-    pub alert: String,
+    #[serde(default)]
+    pub alert: Alert,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub colormode: Option<String>,
+    pub colormode: Option<ColorMode>,
+    #[serde(default)]
     pub mode: String,
+    #[serde(default)]
     pub reachable: bool,
 }
 
@@ -89,54 +400,392 @@ impl From<State> for SendableState {
             sat: state.sat,
             effect: state.effect,
             xy: state.xy,
+            ct: state.ct.and_then(|ct| u16::try_from(ct).ok()),
             alert: None,
             transitiontime: Some(1),
             colormode: state.colormode,
+            ..Self::default()
+        }
+    }
+}
+
+/// The kind of a light, as reported in its `type` field
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(from = "String", into = "String")]
+pub enum LightType {
+    /// Color and color temperature (`Extended color light`)
+    ExtendedColor,
+    /// Color only (`Color light`)
+    Color,
+    /// White with a tunable color temperature (`Color temperature light`)
+    ColorTemperature,
+    /// White with brightness only (`Dimmable light`)
+    Dimmable,
+    /// Smart plugs and switches without dimming (`On/Off plug-in unit`)
+    OnOffPlug,
+    /// Any type not known by this crate, as reported by the bridge
+    Other(String),
+}
+
+impl From<String> for LightType {
+    fn from(kind: String) -> Self {
+        match &kind[..] {
+            "Extended color light" => Self::ExtendedColor,
+            "Color light" => Self::Color,
+            "Color temperature light" => Self::ColorTemperature,
+            "Dimmable light" => Self::Dimmable,
+            "On/Off plug-in unit" => Self::OnOffPlug,
+            _ => Self::Other(kind),
+        }
+    }
+}
+
+impl From<LightType> for String {
+    fn from(kind: LightType) -> Self {
+        match kind {
+            LightType::ExtendedColor => String::from("Extended color light"),
+            LightType::Color => String::from("Color light"),
+            LightType::ColorTemperature => String::from("Color temperature light"),
+            LightType::Dimmable => String::from("Dimmable light"),
+            LightType::OnOffPlug => String::from("On/Off plug-in unit"),
+            LightType::Other(kind) => kind,
         }
     }
 }
 
+/// Light struct representing the complete state of a light.
+///
+/// Only the fields every light reports are required, the rest differ between
+/// light types and vendors.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Light {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: LightType,
+    pub state: State,
+    pub modelid: String,
+    pub manufacturername: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub productname: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub productid: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uniqueid: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub swversion: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub swconfigid: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub luminaireuniqueid: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub swupdate: Option<LightSoftwareUpdate>,
+    #[serde(default)]
+    pub capabilities: Capabilities,
+    #[serde(default)]
+    pub config: LightConfig,
+}
+
 impl std::fmt::Display for Light {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match &*self {
-            Self::LightBulb { state, name, .. } => write!(f, "{{ on: {} }} : {}", state.on, name),
-            Self::LightStrip { state, name, .. } => write!(f, "{{ on: {} }} : {}", state.on, name),
+        write!(f, "{{ on: {} }} : {}", self.state.on, self.name)
+    }
+}
+
+/// The color gamut of a light, see the Hue documentation for the corners of each
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GamutType {
+    A,
+    B,
+    C,
+    /// Any gamut not known by this crate, `colorgamut` holds its corners
+    #[serde(other)]
+    Other,
+}
+
+impl GamutType {
+    /// Corners of the gamut, `None` for `Other`
+    pub fn gamut(self) -> Option<Gamut> {
+        match self {
+            GamutType::A => Some(Gamut::A),
+            GamutType::B => Some(Gamut::B),
+            GamutType::C => Some(Gamut::C),
+            GamutType::Other => None,
         }
     }
 }
 
-/// Light enum representing the complete state of possible lights
-#[derive(Serialize, Deserialize, Debug, Clone)]
-#[serde(untagged)]
-pub enum Light {
-    LightBulb {
-        state: State,
-        swupdate: Value,
-        r#type: String,
-        name: String,
-        modelid: String,
-        manufacturername: String,
-        productname: String,
-        capabilities: Value,
-        config: Value,
-        uniqueid: String,
-        swversion: String,
-        swconfigid: String,
-        productid: String,
-    },
-    LightStrip {
-        state: State,
-        swupdate: Value,
-        r#type: String,
-        name: String,
-        modelid: String,
-        manufacturername: String,
-        productname: String,
-        capabilities: Value,
-        config: Value,
-        uniqueid: String,
-        swversion: String,
-    },
+/// A triangle of CIE xy colors a light is able to produce.
+///
+/// ```
+/// use lighthouse::lights::Gamut;
+/// assert!(Gamut::C.contains([0.3127, 0.329]));
+/// let green = Gamut::B.closest([0.17, 0.7]);
+/// assert!(Gamut::B.contains(green));
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(from = "[[f32; 2]; 3]", into = "[[f32; 2]; 3]")]
+pub struct Gamut {
+    pub red: [f32; 2],
+    pub green: [f32; 2],
+    pub blue: [f32; 2],
+}
+
+impl Gamut {
+    /// Gamut of the first color lights, LivingColors and lightstrips
+    pub const A: Gamut = Gamut {
+        red: [0.704, 0.296],
+        green: [0.2151, 0.7106],
+        blue: [0.138, 0.08],
+    };
+    /// Gamut of the first generations of Hue color bulbs
+    pub const B: Gamut = Gamut {
+        red: [0.675, 0.322],
+        green: [0.409, 0.518],
+        blue: [0.167, 0.04],
+    };
+    /// Gamut of the current Hue color lights
+    pub const C: Gamut = Gamut {
+        red: [0.6915, 0.3083],
+        green: [0.17, 0.7],
+        blue: [0.1532, 0.0475],
+    };
+
+    /// True if the color is inside of the triangle or on its edges, up to rounding
+    pub fn contains(&self, xy: [f32; 2]) -> bool {
+        const EPSILON: f32 = 1e-6;
+        let (d1, d2, d3) = (
+            cross(self.red, self.green, xy),
+            cross(self.green, self.blue, xy),
+            cross(self.blue, self.red, xy),
+        );
+        let negative = d1 < -EPSILON || d2 < -EPSILON || d3 < -EPSILON;
+        let positive = d1 > EPSILON || d2 > EPSILON || d3 > EPSILON;
+        !(negative && positive)
+    }
+
+    /// The color itself if the light can produce it, the closest point of the
+    /// triangle otherwise
+    pub fn closest(&self, xy: [f32; 2]) -> [f32; 2] {
+        if self.contains(xy) {
+            return xy;
+        }
+        [
+            closest_on_segment(xy, self.red, self.green),
+            closest_on_segment(xy, self.green, self.blue),
+            closest_on_segment(xy, self.blue, self.red),
+        ]
+        .iter()
+        .copied()
+        .min_by(|a, b| distance(xy, *a).total_cmp(&distance(xy, *b)))
+        .unwrap_or(xy)
+    }
+}
+
+impl From<[[f32; 2]; 3]> for Gamut {
+    fn from([red, green, blue]: [[f32; 2]; 3]) -> Self {
+        Self { red, green, blue }
+    }
+}
+
+impl From<Gamut> for [[f32; 2]; 3] {
+    fn from(gamut: Gamut) -> Self {
+        [gamut.red, gamut.green, gamut.blue]
+    }
+}
+
+fn cross(o: [f32; 2], a: [f32; 2], b: [f32; 2]) -> f32 {
+    (a[0] - o[0]) * (b[1] - o[1]) - (a[1] - o[1]) * (b[0] - o[0])
+}
+
+fn closest_on_segment(p: [f32; 2], a: [f32; 2], b: [f32; 2]) -> [f32; 2] {
+    let ab = [b[0] - a[0], b[1] - a[1]];
+    let length = ab[0] * ab[0] + ab[1] * ab[1];
+    if length == 0.0 {
+        return a;
+    }
+    let t = (((p[0] - a[0]) * ab[0] + (p[1] - a[1]) * ab[1]) / length).clamp(0.0, 1.0);
+    [a[0] + ab[0] * t, a[1] + ab[1] * t]
+}
+
+pub(crate) fn distance(a: [f32; 2], b: [f32; 2]) -> f32 {
+    ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2)).sqrt()
+}
+
+/// Range of color temperatures supported by a light, in mireds
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CtRange {
+    pub min: u16,
+    pub max: u16,
+}
+
+/// Limits of the light output
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Control {
+    /// Lowest brightness the light can produce, in hundredths of a percent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mindimlevel: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maxlumen: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub colorgamuttype: Option<GamutType>,
+    /// Red, green and blue corners of the gamut in CIE xy coordinates
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub colorgamut: Option<[[f32; 2]; 3]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ct: Option<CtRange>,
+}
+
+/// Entertainment streaming support
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Streaming {
+    pub renderer: bool,
+    pub proxy: bool,
+}
+
+/// What a light is able to do
+///
+/// ```
+/// use lighthouse::lights::Capabilities;
+/// let capabilities: Capabilities = serde_json::from_str(
+///     r#"{"certified": true, "control": {"ct": {"min": 153, "max": 454}}}"#,
+/// ).unwrap();
+/// assert!(capabilities.supports_ct());
+/// assert!(!capabilities.supports_color());
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Capabilities {
+    #[serde(default)]
+    pub certified: bool,
+    #[serde(default)]
+    pub control: Control,
+    #[serde(default)]
+    pub streaming: Streaming,
+}
+
+impl Capabilities {
+    /// True if the light accepts `xy` or `hue`/`sat` colors
+    pub fn supports_color(&self) -> bool {
+        self.control.colorgamuttype.is_some() || self.control.colorgamut.is_some()
+    }
+
+    /// True if the light accepts color temperatures
+    pub fn supports_ct(&self) -> bool {
+        self.control.ct.is_some()
+    }
+
+    /// Colors the light can produce, from its corners or else its gamut type
+    pub fn gamut(&self) -> Option<Gamut> {
+        self.control
+            .colorgamut
+            .map(Gamut::from)
+            .or_else(|| self.control.colorgamuttype.and_then(GamutType::gamut))
+    }
+}
+
+/// Configuration of a light
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct LightConfig {
+    /// Kind of fixture, e.g. `sultanbulb` or `huelightstrip`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archetype: Option<String>,
+    /// One of `functional`, `decorative` or `mixed`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub function: Option<String>,
+    /// One of `omnidirectional`, `upwards`, `downwards`, `horizontal` or `vertical`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub direction: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub startup: Option<StartupBehavior>,
+}
+
+/// Software update state of a light
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LightSoftwareUpdate {
+    /// One of `noupdates`, `notupdatable`, `transferring` or `readytoinstall`
+    pub state: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lastinstall: Option<String>,
+}
+
+/// What a light does when it gets power back
+///
+/// ```
+/// use lighthouse::{lights::*, state};
+/// let warm = StartupBehavior::Custom(SendableState {
+///     transitiontime: None,
+///     ..state!(nonref; bri: 200, xy: [0.4573, 0.41])
+/// });
+/// let config = LightConfigUpdate { startup: Some(warm) };
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(
+    tag = "mode",
+    content = "customsettings",
+    rename_all = "lowercase",
+    from = "StartupObject"
+)]
+pub enum StartupBehavior {
+    /// Turns on at full brightness in a warm white
+    Safety,
+    /// Goes back to the state it had before the power failure
+    Powerfail,
+    /// Goes back to the last state it had while on
+    LastOnState,
+    /// Turns on with the given settings, the bridge only accepts `bri` and one
+    /// of `xy`, `ct` or `hue`/`sat` (so `transitiontime` should be `None`)
+    Custom(SendableState),
+    /// A mode not known by this crate, or not configured yet
+    Unknown,
+}
+
+/// The `startup` object as sent by the bridge, some firmwares leave `mode` out
+/// until the startup behavior is configured
+#[derive(Deserialize)]
+struct StartupObject {
+    #[serde(default)]
+    mode: Option<String>,
+    #[serde(default)]
+    customsettings: Option<SendableState>,
+}
+
+impl From<StartupObject> for StartupBehavior {
+    fn from(startup: StartupObject) -> Self {
+        match (startup.mode.as_deref(), startup.customsettings) {
+            (Some("safety"), _) => Self::Safety,
+            (Some("powerfail"), _) => Self::Powerfail,
+            (Some("lastonstate"), _) => Self::LastOnState,
+            (Some("custom"), Some(settings)) => Self::Custom(settings),
+            _ => Self::Unknown,
+        }
+    }
+}
+
+/// Changes to the configuration of a light, only the fields that are `Some` are sent
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct LightConfigUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub startup: Option<StartupBehavior>,
+}
+
+/// Conversion used by the `state!` macro for the values of the fields, so that
+/// `transitiontime` can be given either in multiples of 100ms or as a `Duration`.
+#[doc(hidden)]
+pub trait IntoStateField<T> {
+    fn into_state_field(self) -> T;
+}
+
+impl<T> IntoStateField<T> for T {
+    fn into_state_field(self) -> T {
+        self
+    }
+}
+
+impl IntoStateField<u16> for std::time::Duration {
+    /// Rounds to the nearest 100ms, saturating at the longest transition possible
+    fn into_state_field(self) -> u16 {
+        let tenths = (self.as_millis() + 50) / 100;
+        u16::try_from(tenths).unwrap_or(u16::MAX)
+    }
 }
 
 /// Super useful macro to create `SendableState`
@@ -148,26 +797,31 @@ pub enum Light {
 ///
 /// // Returns a value, still useful.
 /// let sendable_state: SendableState = state!(nonref; on: true, xy: [1.0, 0.0]);
+///
+/// // `transitiontime` also accepts a `Duration`
+/// let sendable_state: &SendableState =
+///     state!(on: true, transitiontime: std::time::Duration::from_secs(60));
+/// assert_eq!(sendable_state.transitiontime, Some(600));
 /// ```
 #[macro_export]
 macro_rules! state {
     ($($i:ident:$v:expr), *) => {
         &$crate::lights::SendableState {
-            $($i: Some($v),) *
+            $($i: Some($crate::lights::IntoStateField::into_state_field($v)),) *
             ..$crate::lights::SendableState::default()
         }
     };
 
     (nonref; $($i:ident:$v:expr),*) => {
         $crate::lights::SendableState {
-            $($i: Some($v),)*
+            $($i: Some($crate::lights::IntoStateField::into_state_field($v)),)*
             ..$crate::lights::SendableState::default()
         }
     };
 
     (from: $state:expr; $($i:ident:$v:expr),*) => {{
         let mut sendable = $crate::lights::SendableState::from($state);
-        $(sendable.$i = Some($v);)*
+        $(sendable.$i = Some($crate::lights::IntoStateField::into_state_field($v));)*
         sendable
     }};
 
@@ -0,0 +1,220 @@
+/// # Response module
+///
+/// This module contains the representations of the responses the bridge sends back
+/// when modifying resources. The Hue API answers with an array where every entry
+/// is either a success or an error for one of the attributes that were sent:
+///
+/// ```text
+/// [{"success":{"/lights/1/state/on":true}},{"error":{"type":201,"address":"/lights/1/state/bri","description":"..."}}]
+/// ```
+// imports
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Error type codes documented by the Hue API
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(from = "u16", into = "u16")]
+pub enum ErrorType {
+    /// 1 - the token is not whitelisted on the bridge
+    UnauthorizedUser,
+    /// 2 - the body is not valid JSON
+    InvalidJson,
+    /// 3 - the resource does not exist
+    ResourceNotAvailable,
+    /// 4 - the method is not available for the resource
+    MethodNotAvailable,
+    /// 5 - required parameters are missing from the body
+    MissingParameters,
+    /// 6 - the parameter is not available for the resource
+    ParameterNotAvailable,
+    /// 7 - the value is invalid for the parameter
+    InvalidValue,
+    /// 8 - the parameter is read only
+    ParameterNotModifiable,
+    /// 11 - too many items in a list
+    TooManyItems,
+    /// 12 - the action requires a connection to the portal
+    PortalConnectionRequired,
+    /// 101 - the link button on the bridge was not pressed
+    LinkButtonNotPressed,
+    /// 201 - the parameter cannot be modified because the device is off
+    DeviceOff,
+    /// 301 - the group table is full
+    GroupTableFull,
+    /// 304 - the device is unreachable
+    DeviceUnreachable,
+    /// 402 - the scene buffer is locked
+    SceneBufferLocked,
+    /// 501 - the sensor list is full
+    SensorListFull,
+    /// 601 - the rule engine is full
+    RuleEngineFull,
+    /// 701 - the schedule list is full
+    ScheduleListFull,
+    /// 901 - the bridge encountered an internal error
+    InternalError,
+    /// Any code not covered by the variants above
+    Other(u16),
+}
+
+impl From<u16> for ErrorType {
+    fn from(code: u16) -> Self {
+        match code {
+            1 => Self::UnauthorizedUser,
+            2 => Self::InvalidJson,
+            3 => Self::ResourceNotAvailable,
+            4 => Self::MethodNotAvailable,
+            5 => Self::MissingParameters,
+            6 => Self::ParameterNotAvailable,
+            7 => Self::InvalidValue,
+            8 => Self::ParameterNotModifiable,
+            11 => Self::TooManyItems,
+            12 => Self::PortalConnectionRequired,
+            101 => Self::LinkButtonNotPressed,
+            201 => Self::DeviceOff,
+            301 => Self::GroupTableFull,
+            304 => Self::DeviceUnreachable,
+            402 => Self::SceneBufferLocked,
+            501 => Self::SensorListFull,
+            601 => Self::RuleEngineFull,
+            701 => Self::ScheduleListFull,
+            901 => Self::InternalError,
+            other => Self::Other(other),
+        }
+    }
+}
+
+impl From<ErrorType> for u16 {
+    fn from(kind: ErrorType) -> Self {
+        match kind {
+            ErrorType::UnauthorizedUser => 1,
+            ErrorType::InvalidJson => 2,
+            ErrorType::ResourceNotAvailable => 3,
+            ErrorType::MethodNotAvailable => 4,
+            ErrorType::MissingParameters => 5,
+            ErrorType::ParameterNotAvailable => 6,
+            ErrorType::InvalidValue => 7,
+            ErrorType::ParameterNotModifiable => 8,
+            ErrorType::TooManyItems => 11,
+            ErrorType::PortalConnectionRequired => 12,
+            ErrorType::LinkButtonNotPressed => 101,
+            ErrorType::DeviceOff => 201,
+            ErrorType::GroupTableFull => 301,
+            ErrorType::DeviceUnreachable => 304,
+            ErrorType::SceneBufferLocked => 402,
+            ErrorType::SensorListFull => 501,
+            ErrorType::RuleEngineFull => 601,
+            ErrorType::ScheduleListFull => 701,
+            ErrorType::InternalError => 901,
+            ErrorType::Other(code) => code,
+        }
+    }
+}
+
+/// A single error reported by the bridge
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ApiError {
+    #[serde(rename = "type")]
+    pub kind: ErrorType,
+    #[serde(default)]
+    pub address: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "hue api error {} at `{}`: {}",
+            u16::from(self.kind),
+            self.address,
+            self.description
+        )
+    }
+}
+
+/// One entry of the response array, either a success or an error
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ResponseItem {
+    /// Usually maps the address of the modified attribute to its new value,
+    /// deletions are confirmed with a plain string instead
+    Success(Value),
+    Error(ApiError),
+}
+
+/// Decoded response from the bridge to a request modifying a resource.
+///
+/// ```
+/// use lighthouse::response::*;
+/// let response: ApiResponse = serde_json::from_str(
+///     r#"[{"success":{"/lights/1/state/on":true}},
+///         {"error":{"type":201,"address":"/lights/1/state/bri","description":"device is off"}}]"#,
+/// ).unwrap();
+/// assert!(!response.is_success());
+/// assert_eq!(response.error_for("bri").unwrap().kind, ErrorType::DeviceOff);
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(transparent)]
+pub struct ApiResponse(pub Vec<ResponseItem>);
+
+impl ApiResponse {
+    /// Iterates over the addresses and values of the successful entries.
+    ///
+    /// Plain string confirmations (as sent for deletions) are yielded as the
+    /// address with a `null` value.
+    pub fn successes(&self) -> impl Iterator<Item = (&str, &Value)> {
+        static NULL: Value = Value::Null;
+        self.0
+            .iter()
+            .filter_map(|item| match item {
+                ResponseItem::Success(value) => Some(value),
+                ResponseItem::Error(_) => None,
+            })
+            .flat_map(|value| -> Box<dyn Iterator<Item = (&str, &Value)>> {
+                match value {
+                    Value::Object(map) => Box::new(map.iter().map(|(k, v)| (k.as_str(), v))),
+                    Value::String(address) => Box::new(std::iter::once((address.as_str(), &NULL))),
+                    _ => Box::new(std::iter::empty()),
+                }
+            })
+    }
+
+    /// Iterates over the error entries
+    pub fn errors(&self) -> impl Iterator<Item = &ApiError> {
+        self.0.iter().filter_map(|item| match item {
+            ResponseItem::Error(error) => Some(error),
+            ResponseItem::Success(_) => None,
+        })
+    }
+
+    /// True if the bridge did not report any errors
+    pub fn is_success(&self) -> bool {
+        self.errors().next().is_none()
+    }
+
+    /// Finds the error reported for a given attribute, matching on the last
+    /// segment of the address (e.g. `bri` for `/lights/1/state/bri`)
+    pub fn error_for(&self, attribute: &str) -> Option<&ApiError> {
+        self.errors()
+            .find(|error| error.address.rsplit('/').next() == Some(attribute))
+    }
+
+    /// Finds the value reported as successfully set for a given attribute,
+    /// matching on the last segment of the address
+    pub fn success_for(&self, attribute: &str) -> Option<&Value> {
+        self.successes()
+            .find(|(address, _)| address.rsplit('/').next() == Some(attribute))
+            .map(|(_, value)| value)
+    }
+
+    /// Turns the first reported error (if any) into an `Error::Api`
+    pub fn into_result(self) -> Result<Self> {
+        if let Some(error) = self.errors().next() {
+            return Err(Error::Api(error.clone()));
+        }
+        Ok(self)
+    }
+}
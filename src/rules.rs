@@ -0,0 +1,239 @@
+/// # Rules module
+///
+/// This module contains the representations of the rules the bridge evaluates on
+/// its own. A rule fires its actions when all of its conditions are met, which keeps
+/// switches and sensors working even when nothing else is running.
+///
+/// ```
+/// use lighthouse::{rules::*, state};
+/// let rule = NewRule::new(
+///     "Dimmer on",
+///     vec![
+///         Condition::eq("/sensors/2/state/buttonevent", 1002),
+///         Condition::dx("/sensors/2/state/lastupdated"),
+///     ],
+///     vec![Action::group_action(1, state!(on: true, bri: 254))],
+/// );
+/// ```
+// imports
+use crate::lights::SendableState;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Comparison operators available in rule conditions
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    /// Equal to the value
+    #[serde(rename = "eq")]
+    Eq,
+    /// Greater than the value
+    #[serde(rename = "gt")]
+    Gt,
+    /// Less than the value
+    #[serde(rename = "lt")]
+    Lt,
+    /// The attribute changed
+    #[serde(rename = "dx")]
+    Dx,
+    /// The attribute changed after a delay given as the value (`PT00:00:10`)
+    #[serde(rename = "ddx")]
+    Ddx,
+    /// The attribute did not change for the time given as the value
+    #[serde(rename = "stable")]
+    Stable,
+    /// The attribute changed within the time given as the value
+    #[serde(rename = "not stable")]
+    NotStable,
+    /// The time is within the interval given as the value (`T08:00:00/T10:00:00`)
+    #[serde(rename = "in")]
+    In,
+    /// The time is outside of the interval given as the value
+    #[serde(rename = "not in")]
+    NotIn,
+}
+
+/// A condition of a rule, on the attribute found at `address`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Condition {
+    pub address: String,
+    pub operator: Operator,
+    /// The bridge expects every value as a string, even numbers and booleans
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+}
+
+impl Condition {
+    /// A condition with an operator and an optional value
+    pub fn new(address: impl Into<String>, operator: Operator, value: Option<String>) -> Self {
+        Self {
+            address: address.into(),
+            operator,
+            value,
+        }
+    }
+
+    /// The attribute equals the value
+    pub fn eq(address: impl Into<String>, value: impl ToString) -> Self {
+        Self::new(address, Operator::Eq, Some(value.to_string()))
+    }
+
+    /// The attribute is greater than the value
+    pub fn gt(address: impl Into<String>, value: impl ToString) -> Self {
+        Self::new(address, Operator::Gt, Some(value.to_string()))
+    }
+
+    /// The attribute is less than the value
+    pub fn lt(address: impl Into<String>, value: impl ToString) -> Self {
+        Self::new(address, Operator::Lt, Some(value.to_string()))
+    }
+
+    /// The attribute changed, usually used on `lastupdated` to react to every event
+    pub fn dx(address: impl Into<String>) -> Self {
+        Self::new(address, Operator::Dx, None)
+    }
+}
+
+/// Methods an action can use to reach its address
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActionMethod {
+    PUT,
+    POST,
+    DELETE,
+}
+
+/// An action of a rule, a request sent by the bridge to itself
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Action {
+    pub address: String,
+    pub method: ActionMethod,
+    pub body: Value,
+}
+
+impl Action {
+    /// An action with an arbitrary body
+    pub fn new(address: impl Into<String>, method: ActionMethod, body: Value) -> Self {
+        Self {
+            address: address.into(),
+            method,
+            body,
+        }
+    }
+
+    /// Sends a state to a light
+    pub fn light_state(id: u8, state: &SendableState) -> Self {
+        Self::new(
+            format!("/lights/{}/state", id),
+            ActionMethod::PUT,
+            serde_json::json!(state),
+        )
+    }
+
+    /// Sends a state to a group
+    pub fn group_action(id: u8, state: &SendableState) -> Self {
+        Self::new(
+            format!("/groups/{}/action", id),
+            ActionMethod::PUT,
+            serde_json::json!(state),
+        )
+    }
+
+    /// Recalls a scene on a group
+    pub fn recall_scene(scene: &str, group: u8) -> Self {
+        Self::new(
+            format!("/groups/{}/action", group),
+            ActionMethod::PUT,
+            serde_json::json!({ "scene": scene }),
+        )
+    }
+
+    /// The body as a light state, if the action targets a light or a group.
+    ///
+    /// Addresses including the API root (`/api/<token>/lights/1/state`), as used by
+    /// schedules, are recognised as well.
+    pub fn state(&self) -> Option<SendableState> {
+        let address = match self.address.strip_prefix("/api/") {
+            Some(rest) => rest.find('/').map_or("", |start| &rest[start..]),
+            None => &self.address[..],
+        };
+        let targets_state = (address.starts_with("/lights/") && address.ends_with("/state"))
+            || (address.starts_with("/groups/") && address.ends_with("/action"));
+        if targets_state {
+            serde_json::from_value(self.body.clone()).ok()
+        } else {
+            None
+        }
+    }
+}
+
+/// Whether a rule is evaluated
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleStatus {
+    Enabled,
+    Disabled,
+    /// A resource the rule depends on was deleted, the rule is disabled
+    ResourceDeleted,
+}
+
+/// A rule as returned by the bridge
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Rule {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lasttriggered: Option<String>,
+    #[serde(default)]
+    pub timestriggered: u32,
+    pub status: RuleStatus,
+    #[serde(default)]
+    pub recycle: bool,
+    pub conditions: Vec<Condition>,
+    pub actions: Vec<Action>,
+}
+
+impl std::fmt::Display for Rule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{{ status: {:?} }} : {}", self.status, self.name)
+    }
+}
+
+/// The body used to create a new rule on the bridge
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct NewRule {
+    pub name: String,
+    pub conditions: Vec<Condition>,
+    pub actions: Vec<Action>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<RuleStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recycle: Option<bool>,
+}
+
+impl NewRule {
+    /// A rule with its conditions and actions, enabled once created
+    pub fn new(name: impl Into<String>, conditions: Vec<Condition>, actions: Vec<Action>) -> Self {
+        Self {
+            name: name.into(),
+            conditions,
+            actions,
+            status: None,
+            recycle: None,
+        }
+    }
+}
+
+/// Changes to an existing rule, only the fields that are `Some` are sent
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct RuleUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<RuleStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conditions: Option<Vec<Condition>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actions: Option<Vec<Action>>,
+}
@@ -0,0 +1,125 @@
+/// # Scenes module
+///
+/// This module contains the representations of the scenes stored on the bridge.
+/// The light states of a scene reuse the `SendableState`, so they can be built
+/// with the `state!` macro.
+// imports
+use crate::lights::SendableState;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The kind of a scene
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SceneType {
+    /// A scene for an arbitrary list of lights, the default for scenes created without a type
+    #[default]
+    LightScene,
+    /// A scene tied to a group, the lights follow the group membership
+    GroupScene,
+}
+
+/// A scene as returned by the bridge.
+///
+/// The `lightstates` are only included when fetching a single scene.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Scene {
+    pub name: String,
+    #[serde(rename = "type", default)]
+    pub kind: SceneType,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "crate::helpers::optional_string_id"
+    )]
+    pub group: Option<u8>,
+    #[serde(with = "crate::helpers::string_ids")]
+    pub lights: Vec<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    #[serde(default)]
+    pub recycle: bool,
+    #[serde(default)]
+    pub locked: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lastupdated: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<u8>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub lightstates: BTreeMap<u8, SendableState>,
+}
+
+impl std::fmt::Display for Scene {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{{ lights: {:?} }} : {}", self.lights, self.name)
+    }
+}
+
+/// The body used to create a new scene on the bridge
+///
+/// ```
+/// use lighthouse::{scenes::*, state};
+/// let scene = NewScene::light_scene("Reading", vec![1, 2])
+///     .with_lightstate(1, state!(nonref; on: true, bri: 254))
+///     .with_lightstate(2, state!(nonref; on: false));
+/// let scene = NewScene::group_scene("Dinner", 3);
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct NewScene {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: SceneType,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "crate::helpers::optional_string_id"
+    )]
+    pub group: Option<u8>,
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        with = "crate::helpers::string_ids"
+    )]
+    pub lights: Vec<u8>,
+    pub recycle: bool,
+    /// When empty the bridge stores the current state of the lights
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub lightstates: BTreeMap<u8, SendableState>,
+}
+
+impl NewScene {
+    /// A scene for a list of lights
+    pub fn light_scene(name: impl Into<String>, lights: Vec<u8>) -> Self {
+        Self {
+            name: name.into(),
+            kind: SceneType::LightScene,
+            group: None,
+            lights,
+            recycle: false,
+            lightstates: BTreeMap::new(),
+        }
+    }
+
+    /// A scene for all the lights of a group
+    pub fn group_scene(name: impl Into<String>, group: u8) -> Self {
+        Self {
+            name: name.into(),
+            kind: SceneType::GroupScene,
+            group: Some(group),
+            lights: Vec::new(),
+            recycle: false,
+            lightstates: BTreeMap::new(),
+        }
+    }
+
+    /// Sets the state a light should have when the scene is recalled
+    pub fn with_lightstate(mut self, light: u8, state: SendableState) -> Self {
+        self.lightstates.insert(light, state);
+        self
+    }
+
+    /// Lets the bridge delete the scene when it runs out of space
+    pub fn recyclable(mut self) -> Self {
+        self.recycle = true;
+        self
+    }
+}
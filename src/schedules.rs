@@ -0,0 +1,422 @@
+/// # Schedules module
+///
+/// This module contains the representations of the schedules stored on the bridge
+/// and of the time patterns the bridge uses to decide when they fire.
+///
+/// Time patterns are typed and converted to and from the string form the bridge
+/// expects, a weekday wake-up at 7 with up to 10 minutes of randomness is:
+///
+/// ```
+/// use lighthouse::schedules::*;
+/// let pattern = TimePattern::recurring(Weekdays::WEEKDAYS, Time::new(7, 0, 0))
+///     .with_random(Time::new(0, 10, 0));
+/// assert_eq!(pattern.to_string(), "W124/T07:00:00A00:10:00");
+/// assert_eq!("W124/T07:00:00A00:10:00".parse::<TimePattern>().unwrap(), pattern);
+/// ```
+// imports
+use crate::{
+    error::{Error, Result},
+    helpers::{parse_date, parse_time},
+    rules::Action,
+};
+use serde::{Deserialize, Serialize};
+use std::{convert::TryFrom, fmt, str::FromStr};
+
+/// The request a schedule sends when it fires, same as the actions of rules.
+///
+/// The bridge expects the address to include the API root (`/api/<token>/lights/1/state`),
+/// addresses relative to it (`/lights/1/state`) are completed when the schedule is sent
+/// through the bridge.
+pub type Command = Action;
+
+/// A calendar date
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Date {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+}
+
+impl Date {
+    pub const fn new(year: u16, month: u8, day: u8) -> Self {
+        Self { year, month, day }
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl FromStr for Date {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (year, month, day) = parse_date(s).ok_or_else(|| invalid(s))?;
+        Ok(Self::new(year, month, day))
+    }
+}
+
+/// A time of day, also used for the durations of timers and random offsets
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Time {
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+}
+
+impl Time {
+    pub const fn new(hour: u8, minute: u8, second: u8) -> Self {
+        Self {
+            hour,
+            minute,
+            second,
+        }
+    }
+}
+
+impl fmt::Display for Time {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}:{:02}:{:02}", self.hour, self.minute, self.second)
+    }
+}
+
+impl FromStr for Time {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (hour, minute, second) = parse_time(s).ok_or_else(|| invalid(s))?;
+        Ok(Self::new(hour, minute, second))
+    }
+}
+
+/// Days of the week a recurring schedule fires on, as the bitmask used by the
+/// bridge (Monday is the highest bit, Sunday the lowest).
+///
+/// ```
+/// use lighthouse::schedules::Weekdays;
+/// let days = Weekdays::MONDAY | Weekdays::FRIDAY;
+/// assert!(days.contains(Weekdays::FRIDAY));
+/// assert!(!days.contains(Weekdays::WEEKEND));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Weekdays(pub u8);
+
+impl Weekdays {
+    pub const MONDAY: Self = Self(64);
+    pub const TUESDAY: Self = Self(32);
+    pub const WEDNESDAY: Self = Self(16);
+    pub const THURSDAY: Self = Self(8);
+    pub const FRIDAY: Self = Self(4);
+    pub const SATURDAY: Self = Self(2);
+    pub const SUNDAY: Self = Self(1);
+    /// Monday to Friday
+    pub const WEEKDAYS: Self = Self(124);
+    /// Saturday and Sunday
+    pub const WEEKEND: Self = Self(3);
+    pub const ALL: Self = Self(127);
+
+    /// True if all the days of `other` are part of these days
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl std::ops::BitOr for Weekdays {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+}
+
+/// When a schedule fires, in one of the forms supported by the bridge.
+///
+/// Every form accepts an optional random offset (`A` suffix) of up to the given
+/// duration added to the time the schedule fires.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(try_from = "String", into = "String")]
+pub enum TimePattern {
+    /// Fires once at a given date and time: `2026-01-01T07:00:00`
+    Absolute {
+        date: Date,
+        time: Time,
+        random: Option<Time>,
+    },
+    /// Fires on given days of the week at a given time: `W127/T07:00:00`
+    Recurring {
+        days: Weekdays,
+        time: Time,
+        random: Option<Time>,
+    },
+    /// Fires once after a duration: `PT00:10:00`
+    Timer {
+        duration: Time,
+        random: Option<Time>,
+    },
+    /// Fires every time a duration elapses, `repeat` times or forever when `None`:
+    /// `R05/PT00:10:00`
+    RecurringTimer {
+        repeat: Option<u8>,
+        duration: Time,
+        random: Option<Time>,
+    },
+}
+
+impl TimePattern {
+    /// Fires once at a given date and time
+    pub fn absolute(date: Date, time: Time) -> Self {
+        Self::Absolute {
+            date,
+            time,
+            random: None,
+        }
+    }
+
+    /// Fires on given days of the week at a given time
+    pub fn recurring(days: Weekdays, time: Time) -> Self {
+        Self::Recurring {
+            days,
+            time,
+            random: None,
+        }
+    }
+
+    /// Fires once after a duration
+    pub fn timer(duration: Time) -> Self {
+        Self::Timer {
+            duration,
+            random: None,
+        }
+    }
+
+    /// Fires every time a duration elapses, `repeat` times or forever when `None`
+    pub fn recurring_timer(repeat: Option<u8>, duration: Time) -> Self {
+        Self::RecurringTimer {
+            repeat,
+            duration,
+            random: None,
+        }
+    }
+
+    /// Adds a random offset of up to `offset` to the time the schedule fires
+    pub fn with_random(mut self, offset: Time) -> Self {
+        match &mut self {
+            Self::Absolute { random, .. }
+            | Self::Recurring { random, .. }
+            | Self::Timer { random, .. }
+            | Self::RecurringTimer { random, .. } => *random = Some(offset),
+        }
+        self
+    }
+}
+
+impl fmt::Display for TimePattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let random = match self {
+            Self::Absolute { date, time, random } => {
+                write!(f, "{}T{}", date, time)?;
+                random
+            }
+            Self::Recurring { days, time, random } => {
+                write!(f, "W{:03}/T{}", days.0, time)?;
+                random
+            }
+            Self::Timer { duration, random } => {
+                write!(f, "PT{}", duration)?;
+                random
+            }
+            Self::RecurringTimer {
+                repeat,
+                duration,
+                random,
+            } => {
+                match repeat {
+                    Some(repeat) => write!(f, "R{:02}/PT{}", repeat, duration)?,
+                    None => write!(f, "R/PT{}", duration)?,
+                }
+                random
+            }
+        };
+        match random {
+            Some(random) => write!(f, "A{}", random),
+            None => Ok(()),
+        }
+    }
+}
+
+impl FromStr for TimePattern {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if let Some(rest) = s.strip_prefix('W') {
+            let (days, rest) = rest.split_once("/T").ok_or_else(|| invalid(s))?;
+            let days = days.parse::<u8>().map_err(|_| invalid(s))?;
+            if days > Weekdays::ALL.0 {
+                return Err(invalid(s));
+            }
+            let (time, random) = split_random(rest)?;
+            Ok(Self::Recurring {
+                days: Weekdays(days),
+                time,
+                random,
+            })
+        } else if let Some(rest) = s.strip_prefix('R') {
+            let (repeat, rest) = rest.split_once("/PT").ok_or_else(|| invalid(s))?;
+            let repeat = match repeat {
+                "" => None,
+                repeat => Some(repeat.parse::<u8>().map_err(|_| invalid(s))?),
+            };
+            let (duration, random) = split_random(rest)?;
+            Ok(Self::RecurringTimer {
+                repeat,
+                duration,
+                random,
+            })
+        } else if let Some(rest) = s.strip_prefix("PT") {
+            let (duration, random) = split_random(rest)?;
+            Ok(Self::Timer { duration, random })
+        } else {
+            let (date, rest) = s.split_once('T').ok_or_else(|| invalid(s))?;
+            let (time, random) = split_random(rest)?;
+            Ok(Self::Absolute {
+                date: date.parse()?,
+                time,
+                random,
+            })
+        }
+    }
+}
+
+impl TryFrom<String> for TimePattern {
+    type Error = Error;
+
+    fn try_from(s: String) -> Result<Self> {
+        s.parse()
+    }
+}
+
+impl From<TimePattern> for String {
+    fn from(pattern: TimePattern) -> Self {
+        pattern.to_string()
+    }
+}
+
+/// Splits `hh:mm:ss[Ahh:mm:ss]` into the time and the optional random offset
+fn split_random(s: &str) -> Result<(Time, Option<Time>)> {
+    match s.split_once('A') {
+        Some((time, random)) => Ok((time.parse()?, Some(random.parse()?))),
+        None => Ok((s.parse()?, None)),
+    }
+}
+
+fn invalid(s: &str) -> Error {
+    Error::InvalidTimePattern(format!("`{}`", s))
+}
+
+/// Whether a schedule is active
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ScheduleStatus {
+    Enabled,
+    Disabled,
+}
+
+/// A schedule as returned by the bridge
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Schedule {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub command: Command,
+    /// When the schedule fires, in the local time of the bridge
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub localtime: Option<TimePattern>,
+    /// Same as `localtime` but in UTC, only sent by older bridges
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time: Option<TimePattern>,
+    pub status: ScheduleStatus,
+    /// Whether the bridge deletes the schedule once it has fired
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub autodelete: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created: Option<String>,
+    /// When the timer of a timer schedule was started
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub starttime: Option<String>,
+    #[serde(default)]
+    pub recycle: bool,
+}
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.localtime {
+            Some(localtime) => write!(f, "{{ {:?}, {} }} : {}", self.status, localtime, self.name),
+            None => write!(f, "{{ {:?} }} : {}", self.status, self.name),
+        }
+    }
+}
+
+/// The body used to create a new schedule on the bridge
+///
+/// ```
+/// use lighthouse::{rules::Action, schedules::*, state};
+/// let wake_up = NewSchedule::new(
+///     "Wake up",
+///     Action::group_action(1, state!(on: true, bri: 254)),
+///     TimePattern::recurring(Weekdays::WEEKDAYS, Time::new(6, 50, 0)),
+/// );
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct NewSchedule {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub command: Command,
+    pub localtime: TimePattern,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<ScheduleStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub autodelete: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recycle: Option<bool>,
+}
+
+impl NewSchedule {
+    /// A schedule sending a command at the given time, enabled once created
+    pub fn new(name: impl Into<String>, command: Command, localtime: TimePattern) -> Self {
+        Self {
+            name: name.into(),
+            description: None,
+            command,
+            localtime,
+            status: None,
+            autodelete: None,
+            recycle: None,
+        }
+    }
+
+    /// Sets the description of the schedule
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+}
+
+/// Changes to an existing schedule, only the fields that are `Some` are sent
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct ScheduleUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<Command>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub localtime: Option<TimePattern>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<ScheduleStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub autodelete: Option<bool>,
+}
@@ -0,0 +1,92 @@
+/// # Search module
+///
+/// This module contains the representations used when searching for new lights
+/// and sensors. A search is started on the bridge, runs for about 40 seconds and
+/// its progress and results are read back from `/lights/new` or `/sensors/new`:
+///
+/// ```text
+/// {"7": {"name": "Hue color lamp 7"}, "lastscan": "2020-07-03T12:34:56"}
+/// ```
+// imports
+use crate::error::Error;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::{collections::BTreeMap, convert::TryFrom, time::Duration};
+
+/// How long to wait for a search to finish, searches take about 40 seconds
+pub const SEARCH_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// State of the last search for new devices
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(from = "String", into = "String")]
+pub enum LastScan {
+    /// No search was done since the bridge was started
+    None,
+    /// A search is running
+    Active,
+    /// The UTC time the last search finished
+    Completed(String),
+}
+
+impl From<String> for LastScan {
+    fn from(value: String) -> Self {
+        match &value[..] {
+            "none" => Self::None,
+            "active" => Self::Active,
+            _ => Self::Completed(value),
+        }
+    }
+}
+
+impl From<LastScan> for String {
+    fn from(value: LastScan) -> Self {
+        match value {
+            LastScan::None => String::from("none"),
+            LastScan::Active => String::from("active"),
+            LastScan::Completed(time) => time,
+        }
+    }
+}
+
+/// A device found by the last search
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct NewDevice {
+    pub name: String,
+}
+
+/// Progress of the last search and the devices it found, mapped by their id
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(try_from = "Map<String, Value>")]
+pub struct NewDevices {
+    pub lastscan: LastScan,
+    pub devices: BTreeMap<u8, NewDevice>,
+}
+
+impl NewDevices {
+    /// True while the bridge is still searching
+    pub fn is_active(&self) -> bool {
+        self.lastscan == LastScan::Active
+    }
+}
+
+impl TryFrom<Map<String, Value>> for NewDevices {
+    type Error = Error;
+
+    // the ids of the devices sit next to `lastscan` in the same object
+    fn try_from(mut map: Map<String, Value>) -> Result<Self, Error> {
+        let lastscan = serde_json::from_value(map.remove("lastscan").unwrap_or(Value::Null))?;
+        let devices = map
+            .into_iter()
+            .map(|(id, device)| {
+                let id = id.parse().map_err(|_| {
+                    Error::Deserialization(serde::de::Error::custom(format!(
+                        "the bridge returned a non numeric device id `{}`",
+                        id
+                    )))
+                })?;
+                Ok((id, serde_json::from_value(device)?))
+            })
+            .collect::<Result<_, Error>>()?;
+        Ok(Self { lastscan, devices })
+    }
+}
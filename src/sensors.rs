@@ -0,0 +1,318 @@
+/// # Sensors module
+///
+/// This module contains the representations of the sensors known to the bridge:
+/// motion sensors, switches, temperature, light level and daylight sensors as well
+/// as the virtual CLIP sensors used by rules.
+///
+/// Every kind of sensor has its own state struct, the configuration is shared and
+/// only the fields relevant to a given sensor are present. Sensors of a type not
+/// known by this crate keep their common fields and an untyped state.
+// imports
+use serde::{
+    de::{self, DeserializeOwned},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+/// Sensor enum tagged by the `type` the bridge reports
+#[derive(Debug, Clone)]
+pub enum Sensor {
+    /// Hue motion sensor
+    ZLLPresence(SensorInfo<PresenceState>),
+    /// Hue dimmer switch
+    ZLLSwitch(SensorInfo<SwitchState>),
+    /// Hue Tap switch
+    ZGPSwitch(SensorInfo<SwitchState>),
+    /// Temperature sensor built into the motion sensor
+    ZLLTemperature(SensorInfo<TemperatureState>),
+    /// Light level sensor built into the motion sensor
+    ZLLLightLevel(SensorInfo<LightLevelState>),
+    /// Virtual sensor computed by the bridge from sunrise and sunset times
+    Daylight(SensorInfo<DaylightState>),
+    /// Virtual sensor holding an integer, usually used by rules
+    CLIPGenericStatus(SensorInfo<GenericStatusState>),
+    /// Virtual sensor holding a boolean, usually used by rules
+    CLIPGenericFlag(SensorInfo<GenericFlagState>),
+    /// Virtual presence sensor
+    CLIPPresence(SensorInfo<PresenceState>),
+    /// Virtual switch
+    CLIPSwitch(SensorInfo<SwitchState>),
+    /// Virtual temperature sensor
+    CLIPTemperature(SensorInfo<TemperatureState>),
+    /// Virtual light level sensor
+    CLIPLightLevel(SensorInfo<LightLevelState>),
+    /// Any sensor type not known by this crate, with the `type` reported by the
+    /// bridge and its state left untyped
+    Other {
+        kind: String,
+        info: SensorInfo<serde_json::Value>,
+    },
+}
+
+impl Sensor {
+    /// The `type` of the sensor as reported by the bridge
+    pub fn kind(&self) -> &str {
+        match self {
+            Self::ZLLPresence(_) => "ZLLPresence",
+            Self::ZLLSwitch(_) => "ZLLSwitch",
+            Self::ZGPSwitch(_) => "ZGPSwitch",
+            Self::ZLLTemperature(_) => "ZLLTemperature",
+            Self::ZLLLightLevel(_) => "ZLLLightLevel",
+            Self::Daylight(_) => "Daylight",
+            Self::CLIPGenericStatus(_) => "CLIPGenericStatus",
+            Self::CLIPGenericFlag(_) => "CLIPGenericFlag",
+            Self::CLIPPresence(_) => "CLIPPresence",
+            Self::CLIPSwitch(_) => "CLIPSwitch",
+            Self::CLIPTemperature(_) => "CLIPTemperature",
+            Self::CLIPLightLevel(_) => "CLIPLightLevel",
+            Self::Other { kind, .. } => kind,
+        }
+    }
+
+    /// The name of the sensor
+    pub fn name(&self) -> &str {
+        self.config_and_name().1
+    }
+
+    /// The configuration of the sensor
+    pub fn config(&self) -> &SensorConfig {
+        self.config_and_name().0
+    }
+
+    fn config_and_name(&self) -> (&SensorConfig, &str) {
+        match self {
+            Self::ZLLPresence(info) | Self::CLIPPresence(info) => (&info.config, &info.name),
+            Self::ZLLSwitch(info) | Self::ZGPSwitch(info) | Self::CLIPSwitch(info) => {
+                (&info.config, &info.name)
+            }
+            Self::ZLLTemperature(info) | Self::CLIPTemperature(info) => (&info.config, &info.name),
+            Self::ZLLLightLevel(info) | Self::CLIPLightLevel(info) => (&info.config, &info.name),
+            Self::Daylight(info) => (&info.config, &info.name),
+            Self::CLIPGenericStatus(info) => (&info.config, &info.name),
+            Self::CLIPGenericFlag(info) => (&info.config, &info.name),
+            Self::Other { info, .. } => (&info.config, &info.name),
+        }
+    }
+}
+
+impl std::fmt::Display for Sensor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (config, name) = self.config_and_name();
+        write!(f, "{{ on: {} }} : {}", config.on.unwrap_or(false), name)
+    }
+}
+
+impl<'de> Deserialize<'de> for Sensor {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        fn info<S: DeserializeOwned, E: de::Error>(
+            value: serde_json::Value,
+        ) -> Result<SensorInfo<S>, E> {
+            serde_json::from_value(value).map_err(E::custom)
+        }
+
+        let value = serde_json::Value::deserialize(deserializer)?;
+        let kind = value
+            .get("type")
+            .and_then(serde_json::Value::as_str)
+            .ok_or_else(|| de::Error::missing_field("type"))?
+            .to_owned();
+        Ok(match &kind[..] {
+            "ZLLPresence" => Self::ZLLPresence(info(value)?),
+            "ZLLSwitch" => Self::ZLLSwitch(info(value)?),
+            "ZGPSwitch" => Self::ZGPSwitch(info(value)?),
+            "ZLLTemperature" => Self::ZLLTemperature(info(value)?),
+            "ZLLLightLevel" => Self::ZLLLightLevel(info(value)?),
+            "Daylight" => Self::Daylight(info(value)?),
+            "CLIPGenericStatus" => Self::CLIPGenericStatus(info(value)?),
+            "CLIPGenericFlag" => Self::CLIPGenericFlag(info(value)?),
+            "CLIPPresence" => Self::CLIPPresence(info(value)?),
+            "CLIPSwitch" => Self::CLIPSwitch(info(value)?),
+            "CLIPTemperature" => Self::CLIPTemperature(info(value)?),
+            "CLIPLightLevel" => Self::CLIPLightLevel(info(value)?),
+            _ => Self::Other {
+                info: info(value)?,
+                kind,
+            },
+        })
+    }
+}
+
+impl Serialize for Sensor {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        /// The sensor info with its `type` put back next to the other fields
+        #[derive(Serialize)]
+        struct Tagged<'a, T> {
+            #[serde(rename = "type")]
+            kind: &'a str,
+            #[serde(flatten)]
+            info: &'a SensorInfo<T>,
+        }
+
+        let kind = self.kind();
+        match self {
+            Self::ZLLPresence(info) | Self::CLIPPresence(info) => {
+                Tagged { kind, info }.serialize(serializer)
+            }
+            Self::ZLLSwitch(info) | Self::ZGPSwitch(info) | Self::CLIPSwitch(info) => {
+                Tagged { kind, info }.serialize(serializer)
+            }
+            Self::ZLLTemperature(info) | Self::CLIPTemperature(info) => {
+                Tagged { kind, info }.serialize(serializer)
+            }
+            Self::ZLLLightLevel(info) | Self::CLIPLightLevel(info) => {
+                Tagged { kind, info }.serialize(serializer)
+            }
+            Self::Daylight(info) => Tagged { kind, info }.serialize(serializer),
+            Self::CLIPGenericStatus(info) => Tagged { kind, info }.serialize(serializer),
+            Self::CLIPGenericFlag(info) => Tagged { kind, info }.serialize(serializer),
+            Self::Other { info, .. } => Tagged { kind, info }.serialize(serializer),
+        }
+    }
+}
+
+/// Information shared by all the sensor kinds, generic over the sensor state
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SensorInfo<S> {
+    pub name: String,
+    pub modelid: String,
+    pub manufacturername: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub productname: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uniqueid: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub swversion: Option<String>,
+    pub state: S,
+    pub config: SensorConfig,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recycle: Option<bool>,
+}
+
+/// State of a presence (motion) sensor
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PresenceState {
+    pub presence: Option<bool>,
+    pub lastupdated: String,
+}
+
+/// State of a switch, `buttonevent` encodes the button and the kind of press
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SwitchState {
+    pub buttonevent: Option<u16>,
+    pub lastupdated: String,
+}
+
+/// State of a temperature sensor, in hundredths of a degree Celsius
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TemperatureState {
+    pub temperature: Option<i16>,
+    pub lastupdated: String,
+}
+
+/// State of a light level sensor, `lightlevel` is `10000 * log10(lux) + 1`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LightLevelState {
+    pub lightlevel: Option<u32>,
+    pub dark: Option<bool>,
+    pub daylight: Option<bool>,
+    pub lastupdated: String,
+}
+
+/// State of the daylight sensor
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DaylightState {
+    pub daylight: Option<bool>,
+    pub lastupdated: String,
+}
+
+/// State of a generic status sensor
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GenericStatusState {
+    pub status: i32,
+    pub lastupdated: String,
+}
+
+/// State of a generic flag sensor
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GenericFlagState {
+    pub flag: bool,
+    pub lastupdated: String,
+}
+
+/// Configuration of a sensor as returned by the bridge, see `SensorConfigUpdate`
+/// to change it
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct SensorConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reachable: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub battery: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alert: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ledindication: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub usertest: Option<bool>,
+    /// Sensitivity of a motion sensor, up to `sensitivitymax`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sensitivity: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sensitivitymax: Option<u8>,
+    /// Light level under which a light level sensor reports `dark`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tholddark: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tholdoffset: Option<u16>,
+    /// Whether the daylight sensor knows its location
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub configured: Option<bool>,
+    /// Minutes added to the sunrise time of the daylight sensor
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sunriseoffset: Option<i8>,
+    /// Minutes added to the sunset time of the daylight sensor
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sunsetoffset: Option<i8>,
+}
+
+/// Changes to the configuration of a sensor, only the fields that are `Some` are sent.
+///
+/// Only the writable fields are available, the bridge rejects the others (e.g.
+/// `battery` or `reachable`) with `ErrorType::ParameterNotModifiable`.
+///
+/// ```
+/// use lighthouse::sensors::SensorConfigUpdate;
+/// let config = SensorConfigUpdate {
+///     sensitivity: Some(2),
+///     ledindication: Some(false),
+///     ..SensorConfigUpdate::default()
+/// };
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct SensorConfigUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alert: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ledindication: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub usertest: Option<bool>,
+    /// Sensitivity of a motion sensor, up to its `sensitivitymax`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sensitivity: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tholddark: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tholdoffset: Option<u16>,
+    /// Longitude of the daylight sensor (`000.0000E`), it is never read back
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub long: Option<String>,
+    /// Latitude of the daylight sensor (`000.0000N`), it is never read back
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lat: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sunriseoffset: Option<i8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sunsetoffset: Option<i8>,
+}
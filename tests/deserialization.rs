@@ -2,20 +2,498 @@ use lighthouse::lights::*;
 
 #[test]
 fn lightbulb_from_json() {
-    if let Ok(Light::LightBulb { .. }) =
-        serde_json::from_str(include_str!("json_examples/light.json"))
-    {
-    } else {
-        panic!("Could not deserialize the lights into the correct struct type: LightBulb")
-    }
+    let light: Light = serde_json::from_str(include_str!("json_examples/light.json")).unwrap();
+    assert_eq!(light.kind, LightType::ExtendedColor);
+    assert_eq!(light.swconfigid.as_deref(), Some("772B0E5E"));
+    assert_eq!(light.state.alert, Alert::Select);
+    assert_eq!(light.state.effect, Some(Effect::None));
+    assert_eq!(light.state.colormode, Some(ColorMode::Ct));
 }
 
 #[test]
 fn lightstrip_from_json() {
-    if let Ok(Light::LightStrip { .. }) =
-        serde_json::from_str(include_str!("json_examples/lightstrip.json"))
-    {
-    } else {
-        panic!("Could not deserialize the lights into the correct struct type: LightBulb")
+    let light: Light = serde_json::from_str(include_str!("json_examples/lightstrip.json")).unwrap();
+    assert_eq!(light.kind, LightType::ExtendedColor);
+    assert_eq!(light.productname.as_deref(), Some("Hue lightstrip plus"));
+    assert!(light.swconfigid.is_none());
+}
+
+#[test]
+fn light_types_from_json() {
+    let parse = |json: &str| -> Light { serde_json::from_str(json).unwrap() };
+
+    let ambiance = parse(include_str!("json_examples/lights/color_temperature.json"));
+    assert_eq!(ambiance.kind, LightType::ColorTemperature);
+    assert!(ambiance.capabilities.supports_ct());
+    assert!(!ambiance.capabilities.supports_color());
+
+    let white = parse(include_str!("json_examples/lights/dimmable.json"));
+    assert_eq!(white.kind, LightType::Dimmable);
+    assert_eq!(white.state.bri, Some(127));
+    assert!(white.state.colormode.is_none());
+
+    let go = parse(include_str!("json_examples/lights/color.json"));
+    assert_eq!(go.kind, LightType::Color);
+    assert_eq!(go.capabilities.control.colorgamuttype, Some(GamutType::A));
+    assert_eq!(go.swupdate.unwrap().lastinstall, None);
+
+    let plug = parse(include_str!("json_examples/lights/plug.json"));
+    assert_eq!(plug.kind, LightType::OnOffPlug);
+    assert!(plug.state.bri.is_none());
+
+    let ikea = parse(include_str!("json_examples/lights/ikea.json"));
+    assert_eq!(ikea.kind, LightType::ColorTemperature);
+    assert_eq!(ikea.manufacturername, "IKEA of Sweden");
+    assert!(ikea.productname.is_none());
+    assert!(ikea.state.mode.is_empty());
+
+    let innr = parse(include_str!("json_examples/lights/innr.json"));
+    assert_eq!(innr.kind, LightType::Dimmable);
+    assert!(innr.swupdate.is_none());
+    assert!(!innr.state.reachable);
+
+    let blinds = parse(include_str!("json_examples/lights/unknown.json"));
+    assert_eq!(
+        blinds.kind,
+        LightType::Other(String::from("Window covering device"))
+    );
+    assert_eq!(
+        serde_json::to_value(&blinds).unwrap()["type"],
+        "Window covering device"
+    );
+}
+
+#[test]
+fn api_response_from_json() {
+    use lighthouse::response::*;
+    let response: ApiResponse = serde_json::from_str(
+        r#"[
+            {"success": {"/lights/1/state/on": true}},
+            {"error": {"type": 201, "address": "/lights/1/state/bri", "description": "parameter, bri, is not modifiable. Device is set to off."}},
+            {"error": {"type": 999, "address": "/lights/1/state/hue", "description": "unknown"}}
+        ]"#,
+    )
+    .unwrap();
+
+    assert!(!response.is_success());
+    assert_eq!(response.success_for("on"), Some(&serde_json::json!(true)));
+    assert_eq!(
+        response.error_for("bri").unwrap().kind,
+        ErrorType::DeviceOff
+    );
+    assert_eq!(
+        response.error_for("hue").unwrap().kind,
+        ErrorType::Other(999)
+    );
+    assert!(response.error_for("on").is_none());
+    match response.into_result() {
+        Err(lighthouse::Error::Api(error)) => assert_eq!(error.kind, ErrorType::DeviceOff),
+        other => panic!("Expected an api error, got {:?}", other),
+    }
+}
+
+#[test]
+fn api_error_type_round_trip() {
+    use lighthouse::response::ErrorType;
+    for code in &[1u16, 3, 7, 101, 201, 304, 901, 42] {
+        assert_eq!(u16::from(ErrorType::from(*code)), *code);
+    }
+}
+
+#[test]
+fn api_error_requires_valid_type() {
+    use lighthouse::response::ApiError;
+    let error: ApiError = serde_json::from_str(r#"{"type": 3, "address": "/lights/9"}"#).unwrap();
+    assert_eq!(error.description, "");
+    for json in &[
+        r#"{"address": "/lights/1", "description": "no type"}"#,
+        r#"{"type": "3", "address": "/lights/1"}"#,
+        r#"{"type": 70000, "address": "/lights/1"}"#,
+    ] {
+        assert!(serde_json::from_str::<ApiError>(json).is_err(), "{}", json);
+    }
+}
+
+#[test]
+fn group_from_json() {
+    use lighthouse::groups::*;
+    let group: Group = serde_json::from_str(include_str!("json_examples/group.json")).unwrap();
+    assert_eq!(group.kind, GroupType::Room);
+    assert_eq!(group.class.as_deref(), Some("Living room"));
+    assert_eq!(group.lights, vec![1, 2, 7]);
+    assert!(group.state.any_on && !group.state.all_on);
+    assert_eq!(group.action.bri, Some(144));
+
+    let source: Group = serde_json::from_str(
+        r#"{"name": "Lightsource 1", "type": "Lightsource", "lights": ["4"], "recycle": false}"#,
+    )
+    .unwrap();
+    assert_eq!(source.kind, GroupType::LightSource);
+    assert_eq!(
+        serde_json::to_value(source.kind).unwrap(),
+        serde_json::json!("Lightsource")
+    );
+}
+
+#[test]
+fn new_group_to_json() {
+    use lighthouse::groups::*;
+    let group = NewGroup::zone("Upstairs", vec![3, 4]).with_class("Bedroom");
+    assert_eq!(
+        serde_json::to_value(&group).unwrap(),
+        serde_json::json!({"name": "Upstairs", "type": "Zone", "class": "Bedroom", "lights": ["3", "4"]})
+    );
+}
+
+#[test]
+fn delete_response_from_json() {
+    use lighthouse::response::*;
+    let response: ApiResponse =
+        serde_json::from_str(r#"[{"success": "/groups/1 deleted."}]"#).unwrap();
+    assert!(response.is_success());
+    assert_eq!(response.successes().count(), 1);
+}
+
+#[test]
+fn scene_from_json() {
+    use lighthouse::scenes::*;
+    let scene: Scene = serde_json::from_str(include_str!("json_examples/scene.json")).unwrap();
+    assert_eq!(scene.kind, SceneType::GroupScene);
+    assert_eq!(scene.group, Some(1));
+    assert_eq!(scene.lights, vec![1, 2]);
+    assert_eq!(scene.lightstates[&1].bri, Some(254));
+    assert_eq!(scene.lightstates[&2].xy, Some([0.4448, 0.4066]));
+}
+
+#[test]
+fn new_scene_to_json() {
+    use lighthouse::{scenes::*, state};
+    let scene = NewScene::light_scene("Reading", vec![1])
+        .with_lightstate(1, state!(nonref; on: true, bri: 254, transitiontime: 4));
+    assert_eq!(
+        serde_json::to_value(&scene).unwrap(),
+        serde_json::json!({
+            "name": "Reading",
+            "type": "LightScene",
+            "lights": ["1"],
+            "recycle": false,
+            "lightstates": {"1": {"on": true, "bri": 254, "transitiontime": 4}}
+        })
+    );
+}
+
+#[test]
+fn sensors_from_json() {
+    use lighthouse::sensors::*;
+    let parse = |json: &str| -> Sensor { serde_json::from_str(json).unwrap() };
+
+    match parse(include_str!("json_examples/sensors/zllpresence.json")) {
+        Sensor::ZLLPresence(info) => {
+            assert_eq!(info.state.presence, Some(false));
+            assert_eq!(info.config.sensitivity, Some(2));
+        }
+        other => panic!("Expected a ZLLPresence sensor, got {:?}", other),
+    }
+    match parse(include_str!("json_examples/sensors/zllswitch.json")) {
+        Sensor::ZLLSwitch(info) => assert_eq!(info.state.buttonevent, Some(1002)),
+        other => panic!("Expected a ZLLSwitch sensor, got {:?}", other),
+    }
+    match parse(include_str!("json_examples/sensors/zgpswitch.json")) {
+        Sensor::ZGPSwitch(info) => assert_eq!(info.state.buttonevent, Some(34)),
+        other => panic!("Expected a ZGPSwitch sensor, got {:?}", other),
+    }
+    match parse(include_str!("json_examples/sensors/zlltemperature.json")) {
+        Sensor::ZLLTemperature(info) => assert_eq!(info.state.temperature, Some(2231)),
+        other => panic!("Expected a ZLLTemperature sensor, got {:?}", other),
+    }
+    match parse(include_str!("json_examples/sensors/zlllightlevel.json")) {
+        Sensor::ZLLLightLevel(info) => {
+            assert_eq!(info.state.lightlevel, Some(14400));
+            assert_eq!(info.config.tholddark, Some(16000));
+        }
+        other => panic!("Expected a ZLLLightLevel sensor, got {:?}", other),
+    }
+    match parse(include_str!("json_examples/sensors/daylight.json")) {
+        Sensor::Daylight(info) => assert_eq!(info.config.sunsetoffset, Some(-30)),
+        other => panic!("Expected a Daylight sensor, got {:?}", other),
     }
+    match parse(include_str!("json_examples/sensors/clipgenericstatus.json")) {
+        Sensor::CLIPGenericStatus(info) => assert_eq!(info.state.status, 2),
+        other => panic!("Expected a CLIPGenericStatus sensor, got {:?}", other),
+    }
+    let flag = parse(include_str!("json_examples/sensors/clipgenericflag.json"));
+    assert_eq!(flag.name(), "Away mode");
+    assert_eq!(
+        serde_json::to_value(&flag).unwrap()["type"],
+        "CLIPGenericFlag"
+    );
+    match flag {
+        Sensor::CLIPGenericFlag(info) => assert!(!info.state.flag),
+        other => panic!("Expected a CLIPGenericFlag sensor, got {:?}", other),
+    }
+}
+
+#[test]
+fn unknown_sensor_from_json() {
+    use lighthouse::sensors::*;
+    let sensor: Sensor =
+        serde_json::from_str(include_str!("json_examples/sensors/zllrelativerotary.json")).unwrap();
+    assert_eq!(sensor.kind(), "ZLLRelativeRotary");
+    assert_eq!(sensor.name(), "Hue tap dial switch 1");
+    assert_eq!(sensor.config().battery, Some(100));
+    match &sensor {
+        Sensor::Other { info, .. } => assert_eq!(info.state["expectedrotation"], 90),
+        other => panic!("Expected an unknown sensor, got {:?}", other),
+    }
+    // the reported type is kept when sending the sensor back
+    let json = serde_json::to_value(&sensor).unwrap();
+    assert_eq!(json["type"], "ZLLRelativeRotary");
+    assert_eq!(json["name"], "Hue tap dial switch 1");
+    assert!(serde_json::from_str::<Sensor>(r#"{"name": "No type"}"#).is_err());
+}
+
+#[test]
+fn sensor_config_to_json() {
+    use lighthouse::sensors::*;
+    let config = SensorConfigUpdate {
+        on: Some(true),
+        sensitivity: Some(1),
+        ..SensorConfigUpdate::default()
+    };
+    assert_eq!(
+        serde_json::to_value(&config).unwrap(),
+        serde_json::json!({"on": true, "sensitivity": 1})
+    );
+}
+
+#[test]
+fn rule_from_json() {
+    use lighthouse::rules::*;
+    let rule: Rule = serde_json::from_str(include_str!("json_examples/rule.json")).unwrap();
+    assert_eq!(rule.status, RuleStatus::Enabled);
+    assert_eq!(rule.timestriggered, 42);
+    assert_eq!(rule.conditions[0].operator, Operator::Eq);
+    assert_eq!(rule.conditions[1].value, None);
+    assert_eq!(rule.conditions[2].operator, Operator::NotIn);
+    assert_eq!(rule.actions[0].state().unwrap().bri, Some(254));
+    assert!(rule.actions[1].state().is_none());
+}
+
+#[test]
+fn new_rule_to_json() {
+    use lighthouse::{rules::*, state};
+    let rule = NewRule::new(
+        "Motion on",
+        vec![Condition::eq("/sensors/4/state/presence", true)],
+        vec![Action::light_state(1, state!(on: true))],
+    );
+    assert_eq!(
+        serde_json::to_value(&rule).unwrap(),
+        serde_json::json!({
+            "name": "Motion on",
+            "conditions": [
+                {"address": "/sensors/4/state/presence", "operator": "eq", "value": "true"}
+            ],
+            "actions": [
+                {"address": "/lights/1/state", "method": "PUT", "body": {"on": true, "transitiontime": 1}}
+            ]
+        })
+    );
+}
+
+#[test]
+fn schedule_from_json() {
+    use lighthouse::schedules::*;
+    let schedule: Schedule =
+        serde_json::from_str(include_str!("json_examples/schedule.json")).unwrap();
+    assert_eq!(schedule.status, ScheduleStatus::Enabled);
+    assert_eq!(
+        schedule.localtime,
+        Some(
+            TimePattern::recurring(Weekdays::WEEKDAYS, Time::new(6, 50, 0))
+                .with_random(Time::new(0, 10, 0))
+        )
+    );
+    assert_eq!(schedule.command.state().unwrap().bri, Some(254));
+}
+
+#[test]
+fn time_patterns_round_trip() {
+    use lighthouse::schedules::*;
+    for pattern in &[
+        "2026-01-01T07:00:00",
+        "2026-01-01T07:00:00A00:30:00",
+        "W127/T07:00:00",
+        "W003/T09:30:00A00:15:00",
+        "PT00:10:00",
+        "PT00:10:00A00:01:00",
+        "R05/PT00:10:00",
+        "R/PT01:00:00",
+        "R10/PT00:00:30A00:00:10",
+    ] {
+        let parsed: TimePattern = pattern.parse().unwrap();
+        assert_eq!(&parsed.to_string(), pattern);
+    }
+    assert_eq!(
+        "R05/PT00:10:00".parse::<TimePattern>().unwrap(),
+        TimePattern::recurring_timer(Some(5), Time::new(0, 10, 0))
+    );
+    for invalid in &[
+        "",
+        "W200/T07:00:00",
+        "PT25:00:00",
+        "2026-13-01T07:00:00",
+        "T07:00",
+    ] {
+        assert!(invalid.parse::<TimePattern>().is_err(), "{}", invalid);
+    }
+}
+
+#[test]
+fn config_from_json() {
+    use lighthouse::config::*;
+    let config: BridgeConfig =
+        serde_json::from_str(include_str!("json_examples/config.json")).unwrap();
+    assert_eq!(config.apiversion, "1.39.0");
+    assert_eq!(config.zigbeechannel, 15);
+    assert_eq!(config.utc.as_deref(), Some("2020-07-10T19:01:02"));
+    assert_eq!(config.whitelist.len(), 2);
+    assert_eq!(
+        config.whitelist["0f607264fc6318a92b9e13c65db7cd3c"].name,
+        "lighthouse"
+    );
+    assert_eq!(config.swupdate2.unwrap().state, "noupdates");
+}
+
+#[test]
+fn public_config_from_json() {
+    use lighthouse::config::*;
+    let config: PublicConfig = serde_json::from_str(
+        r#"{"name":"Philips hue","datastoreversion":"90","swversion":"1939139020",
+            "apiversion":"1.39.0","mac":"00:17:88:23:bf:c2","bridgeid":"001788FFFE23BFC2",
+            "factorynew":false,"replacesbridgeid":null,"modelid":"BSB002","starterkitid":""}"#,
+    )
+    .unwrap();
+    assert_eq!(config.bridgeid, "001788FFFE23BFC2");
+    assert_eq!(config.replacesbridgeid, None);
+}
+
+#[test]
+fn whitelist_idle_time() {
+    use lighthouse::config::*;
+    let config: BridgeConfig =
+        serde_json::from_str(include_str!("json_examples/config.json")).unwrap();
+    let now = config.utc.clone().unwrap();
+    let idle = |token: &str| config.whitelist[token].idle_time(&now).unwrap().as_secs();
+    assert_eq!(idle("0f607264fc6318a92b9e13c65db7cd3c"), 0);
+    // 2019-12-24T18:00:00 to 2020-07-10T19:01:02, across a leap day
+    assert_eq!(
+        idle("83b7780291a6ceffbe0bd049104df"),
+        199 * 24 * 60 * 60 + 61 * 60 + 2
+    );
+    let entry = WhitelistEntry {
+        name: String::from("lighthouse"),
+        created: String::from("none"),
+        last_used: String::from("none"),
+    };
+    assert!(entry.idle_time(&now).is_none());
+}
+
+#[test]
+fn new_devices_from_json() {
+    use lighthouse::search::*;
+    let found: NewDevices = serde_json::from_str(
+        r#"{"7": {"name": "Hue color lamp 7"}, "8": {"name": "Hue lamp 8"},
+            "lastscan": "2020-07-03T12:34:56"}"#,
+    )
+    .unwrap();
+    assert!(!found.is_active());
+    assert_eq!(
+        found.lastscan,
+        LastScan::Completed(String::from("2020-07-03T12:34:56"))
+    );
+    assert_eq!(found.devices[&7].name, "Hue color lamp 7");
+    assert_eq!(found.devices.len(), 2);
+
+    let searching: NewDevices = serde_json::from_str(r#"{"lastscan": "active"}"#).unwrap();
+    assert!(searching.is_active());
+    assert!(searching.devices.is_empty());
+    assert!(serde_json::from_str::<NewDevices>(r#"{"lastscan": "none", "x": {}}"#).is_err());
+}
+
+#[test]
+fn startup_behavior_json() {
+    let config = LightConfigUpdate {
+        startup: Some(StartupBehavior::Custom(SendableState {
+            transitiontime: None,
+            ..lighthouse::state!(nonref; bri: 200, xy: [0.5, 0.25])
+        })),
+    };
+    assert_eq!(
+        serde_json::to_value(&config).unwrap(),
+        serde_json::json!({"startup": {"mode": "custom", "customsettings": {"bri": 200, "xy": [0.5, 0.25]}}})
+    );
+
+    let startup: StartupBehavior =
+        serde_json::from_str(r#"{"mode": "safety", "configured": true}"#).unwrap();
+    assert_eq!(startup, StartupBehavior::Safety);
+    let startup: StartupBehavior =
+        serde_json::from_str(r#"{"mode": "lastonstate", "configured": true}"#).unwrap();
+    assert_eq!(startup, StartupBehavior::LastOnState);
+    let startup: StartupBehavior = serde_json::from_str(r#"{"mode": "brandnew"}"#).unwrap();
+    assert_eq!(startup, StartupBehavior::Unknown);
+    let startup: StartupBehavior = serde_json::from_str(r#"{"configured": false}"#).unwrap();
+    assert_eq!(startup, StartupBehavior::Unknown);
+    let startup: StartupBehavior =
+        serde_json::from_str(r#"{"mode": "custom", "customsettings": {"bri": 200, "ct": 366}}"#)
+            .unwrap();
+    assert_eq!(
+        startup,
+        StartupBehavior::Custom(SendableState {
+            transitiontime: None,
+            ..lighthouse::state!(nonref; bri: 200, ct: 366)
+        })
+    );
+
+    // a light whose startup behavior is not configured yet still deserializes
+    let mut light: serde_json::Value =
+        serde_json::from_str(include_str!("json_examples/light.json")).unwrap();
+    light["config"]["startup"] = serde_json::json!({"configured": false});
+    let light: Light = serde_json::from_value(light).unwrap();
+    assert_eq!(light.config.startup, Some(StartupBehavior::Unknown));
+}
+
+#[test]
+fn light_capabilities_from_json() {
+    let light: Light = serde_json::from_str(include_str!("json_examples/light.json")).unwrap();
+    let capabilities = &light.capabilities;
+    assert!(capabilities.supports_color());
+    assert!(capabilities.supports_ct());
+    assert_eq!(capabilities.control.colorgamuttype, Some(GamutType::C));
+    assert_eq!(
+        capabilities.control.ct,
+        Some(CtRange { min: 153, max: 500 })
+    );
+    assert_eq!(capabilities.control.maxlumen, Some(806));
+    assert!(capabilities.streaming.renderer);
+    assert_eq!(light.config.archetype.as_deref(), Some("sultanbulb"));
+    assert_eq!(light.config.startup, Some(StartupBehavior::Safety));
+}
+
+#[test]
+fn state_enums_json() {
+    let state: SendableState =
+        serde_json::from_str(r#"{"alert": "lselect", "effect": "colorloop", "colormode": "hs"}"#)
+            .unwrap();
+    assert_eq!(state.alert, Some(Alert::LSelect));
+    assert_eq!(state.effect, Some(Effect::ColorLoop));
+    assert_eq!(state.colormode, Some(ColorMode::Hs));
+    assert_eq!(
+        serde_json::to_value(&state).unwrap(),
+        serde_json::json!({"alert": "lselect", "effect": "colorloop", "colormode": "hs"})
+    );
+
+    let state: SendableState = serde_json::from_str(r#"{"effect": "sparkle"}"#).unwrap();
+    assert_eq!(state.effect, Some(Effect::Unknown));
+    assert!(state.validate().is_err());
 }
@@ -47,37 +47,39 @@ fn test_empty_state_macro() {
 }
 
 #[test]
+#[allow(clippy::needless_update)]
 fn test_state_macro() {
     use lighthouse::{lights::*, *};
     let ref_state = state!(on: true,
                                    bri: 230,
                                    hue: 100,
                                    sat: 20,
-                                   effect: String::from("none"),
+                                   effect: Effect::None,
                                    xy: [1.0, 1.0],
-                                   alert: String::from("none"),
-                                   colormode: String::from("xy"),
+                                   alert: Alert::None,
+                                   colormode: ColorMode::Xy,
                                    transitiontime: 2);
     let nonref_state = state!(nonref;
                                    on: true,
                                    bri: 230,
                                    hue: 100,
                                    sat: 20,
-                                   effect: String::from("none"),
+                                   effect: Effect::None,
                                    xy: [1.0, 1.0],
-                                   alert: String::from("none"),
-                                   colormode: String::from("xy"),
+                                   alert: Alert::None,
+                                   colormode: ColorMode::Xy,
                                    transitiontime: 2);
     let truth = SendableState {
         on: Some(true),
         bri: Some(230),
         hue: Some(100),
         sat: Some(20),
-        effect: Some(String::from("none")),
+        effect: Some(Effect::None),
         xy: Some([1.0, 1.0]),
-        alert: Some(String::from("none")),
-        colormode: Some(String::from("xy")),
+        alert: Some(Alert::None),
+        colormode: Some(ColorMode::Xy),
         transitiontime: Some(2),
+        ..SendableState::default()
     };
     assert_eq!(ref_state, &truth);
     assert_eq!(nonref_state, truth);
@@ -91,11 +93,11 @@ fn test_from_state() {
         bri: Some(100),
         hue: Some(240),
         sat: Some(20),
-        effect: Some(String::from("none")),
+        effect: Some(Effect::None),
         xy: Some([2.0, 2.0]),
         ct: Some(200),
-        alert: String::from("select"),
-        colormode: Some(String::from("somemode")),
+        alert: Alert::Select,
+        colormode: Some(ColorMode::Hs),
         mode: String::from("mode"),
         reachable: true,
     };
@@ -106,6 +108,55 @@ fn test_from_state() {
 
     s.on = false;
     assert_eq!(SendableState::from(s), state_changed);
+    assert_eq!(state_default.ct, Some(200));
+}
+
+#[test]
+fn test_state_macro_increments_and_duration() {
+    use lighthouse::state;
+    use std::time::Duration;
+    let dim = state!(nonref; bri_inc: -30, transitiontime: Duration::from_millis(4560));
+    assert_eq!(dim.bri_inc, Some(-30));
+    assert_eq!(dim.transitiontime, Some(46));
+    assert_eq!(
+        state!(nonref; transitiontime: Duration::from_secs(3600)).transitiontime,
+        Some(36000)
+    );
+    assert_eq!(
+        state!(nonref; transitiontime: Duration::from_secs(86400)).transitiontime,
+        Some(u16::MAX)
+    );
+    assert_eq!(
+        serde_json::to_value(state!(ct_inc: 20, xy_inc: [0.5, -0.25])).unwrap(),
+        serde_json::json!({"ct_inc": 20, "xy_inc": [0.5, -0.25], "transitiontime": 1})
+    );
+}
+
+#[test]
+fn test_state_validation() {
+    use lighthouse::{lights::*, *};
+    assert!(state!(on: true, bri: 1, sat: 254, ct: 153, xy: [0.0, 1.0])
+        .validate()
+        .is_ok());
+    assert!(state!(bri_inc: -254, hue_inc: 65534, xy_inc: [-0.5, 0.5])
+        .validate()
+        .is_ok());
+    for invalid in &[
+        state!(nonref; bri: 0),
+        state!(nonref; bri: 255),
+        state!(nonref; sat: 255),
+        state!(nonref; ct: 100),
+        state!(nonref; ct: 501),
+        state!(nonref; xy: [-0.1, 0.5]),
+        state!(nonref; bri_inc: 300),
+        state!(nonref; xy_inc: [0.6, 0.0]),
+    ] {
+        match invalid.validate() {
+            Err(Error::InvalidState(_)) => {}
+            other => panic!("{:?} should be invalid, got {:?}", invalid, other),
+        }
+    }
+    assert!(SendableState::checked(state!(nonref; bri: 100)).is_ok());
 }
 
 #[test]
@@ -123,3 +174,629 @@ fn test_bridge_serialization() {
 
     assert!(b.target == b2.target);
 }
+
+#[test]
+#[cfg(feature = "color")]
+fn test_hex_to_rgb() {
+    use lighthouse::color::hex_to_rgb;
+    assert_eq!(hex_to_rgb("ff8800").unwrap(), [255, 136, 0]);
+    assert_eq!(hex_to_rgb("#ff8800").unwrap(), [255, 136, 0]);
+    assert!(hex_to_rgb("ff880").is_err());
+    assert!(hex_to_rgb("gg8800").is_err());
+    assert!(hex_to_rgb("ffé00").is_err());
+}
+
+#[test]
+fn test_bridge_is_thread_safe() {
+    use lighthouse::{bridge::*, lights::SendableState};
+    fn assert_send_sync<T: Send + Sync>() {}
+    fn assert_send<T: Send>(_: &T) {}
+
+    assert_send_sync::<Bridge>();
+    assert_send_sync::<AsyncBridge>();
+
+    // futures of the async bridge have to be spawnable on a multithreaded runtime
+    let bridge = AsyncBridge::new("127.0.0.1".parse().unwrap(), "<SOME-KEY>".to_owned()).unwrap();
+    let state = SendableState::default();
+    assert_send(&bridge.get_lights());
+    assert_send(&bridge.state_to(1, &state));
+    assert_send(&bridge.to_all(&state));
+}
+
+#[test]
+fn test_bridge_builder_target() {
+    use lighthouse::bridge::BridgeBuilder;
+    use url::Url;
+
+    let bridge = BridgeBuilder::new()
+        .host("hue.local")
+        .port(8080)
+        .scheme("https")
+        .token("tokengoeshere1234")
+        .timeout(std::time::Duration::from_secs(1))
+        .build()
+        .unwrap();
+    assert_eq!(
+        bridge.target,
+        Url::parse("https://hue.local:8080/api/tokengoeshere1234/").unwrap()
+    );
+
+    let bridge = BridgeBuilder::new()
+        .ip("::1".parse().unwrap())
+        .token("tokengoeshere1234")
+        .build()
+        .unwrap();
+    assert_eq!(
+        bridge.target,
+        Url::parse("http://[::1]/api/tokengoeshere1234/").unwrap()
+    );
+
+    assert!(BridgeBuilder::new().host("hue.local").build().is_err());
+    assert!(BridgeBuilder::new().token("token").build().is_err());
+}
+
+#[test]
+#[cfg(feature = "persist")]
+fn test_bridge_serialization_with_port() {
+    use lighthouse::*;
+
+    let filename = "test_bridge_with_port";
+    let b = bridge::Bridge::builder()
+        .host("localhost")
+        .port(8080)
+        .token("<SOME-KEY>")
+        .build()
+        .unwrap();
+
+    b.to_file(filename).expect("Could not save bridge to file");
+    let b2 = bridge::Bridge::from_file(filename).unwrap();
+    std::fs::remove_file(filename).unwrap();
+
+    assert_eq!(b.target, b2.target);
+}
+
+#[test]
+fn test_state_builder() {
+    use lighthouse::{lights::*, *};
+    use std::time::Duration;
+    let state = SendableState::builder()
+        .off()
+        .brightness_percent(100.0)
+        .hue_degrees(-90.0)
+        .saturation_percent(50.0)
+        .transition(Duration::from_millis(250))
+        .build()
+        .unwrap();
+    assert_eq!(state.on, Some(false));
+    assert_eq!(state.bri, Some(254));
+    assert_eq!(state.hue, Some(49151));
+    assert_eq!(state.sat, Some(127));
+    assert_eq!(state.transitiontime, Some(3));
+
+    assert_eq!(
+        SendableState::builder()
+            .brightness_percent(0.0)
+            .build()
+            .unwrap()
+            .bri,
+        Some(1)
+    );
+    match SendableState::builder().bri(0).build() {
+        Err(Error::InvalidState(_)) => {}
+        other => panic!("expected an invalid state, got {:?}", other),
+    }
+}
+
+#[test]
+#[cfg(feature = "color")]
+fn test_state_builder_colors() {
+    use lighthouse::{lights::*, *};
+    let orange = SendableState::builder().hex("#ff8800").build().unwrap();
+    assert_eq!(
+        orange.xy,
+        SendableState::builder()
+            .rgb(255, 136, 0)
+            .build()
+            .unwrap()
+            .xy
+    );
+    match SendableState::builder().hex("#ff88").on().build() {
+        Err(Error::InvalidColor(_)) => {}
+        other => panic!("expected an invalid color, got {:?}", other),
+    }
+    assert_eq!(
+        SendableState::builder().kelvin(1000).build().unwrap().ct,
+        Some(500)
+    );
+    assert_eq!(
+        SendableState::builder().kelvin(6500).build().unwrap().ct,
+        Some(154)
+    );
+}
+
+#[test]
+#[cfg(feature = "color")]
+fn test_adapt_state() {
+    use lighthouse::{adapt::adapt_state, lights::*, state};
+    let parse = |json: &str| -> Light { serde_json::from_str(json).unwrap() };
+    let orange = state!(on: true, bri: 200, xy: [0.5612, 0.4042], transitiontime: 4);
+
+    let extended = parse(include_str!("json_examples/light.json"));
+    assert_eq!(&adapt_state(orange, &extended), orange);
+
+    let ambiance = parse(include_str!("json_examples/lights/color_temperature.json"));
+    let adapted = adapt_state(orange, &ambiance);
+    assert_eq!(adapted.xy, None);
+    assert_eq!(adapted.ct, Some(454)); // warmest this bulb can go
+    assert_eq!(adapted.bri, Some(200));
+    let daylight = adapt_state(state!(xy: [0.3127, 0.329]), &ambiance);
+    assert!((153..=160).contains(&daylight.ct.unwrap()));
+    let red = adapt_state(state!(hue: 0, sat: 254), &ambiance);
+    assert_eq!((red.hue, red.sat, red.ct), (None, None, Some(454)));
+    // a malformed range reported by the bridge does not panic
+    let mut inverted = ambiance.clone();
+    inverted.capabilities.control.ct = Some(CtRange { min: 454, max: 153 });
+    assert_eq!(adapt_state(state!(ct: 300), &inverted).ct, Some(454));
+    assert_eq!(adapt_state(orange, &inverted).ct, Some(454));
+
+    let white = parse(include_str!("json_examples/lights/dimmable.json"));
+    let adapted = adapt_state(orange, &white);
+    assert_eq!(
+        (adapted.xy, adapted.ct, adapted.bri),
+        (None, None, Some(200))
+    );
+
+    let plug = parse(include_str!("json_examples/lights/plug.json"));
+    let adapted = adapt_state(orange, &plug);
+    assert_eq!(
+        adapted,
+        SendableState {
+            on: Some(true),
+            transitiontime: None,
+            ..SendableState::default()
+        }
+    );
+
+    let go = parse(include_str!("json_examples/lights/color.json"));
+    let [x, y] = adapt_state(state!(ct: 370), &go).xy.unwrap();
+    assert!((x - 0.45).abs() < 0.01 && (y - 0.41).abs() < 0.01);
+    // pure green is outside of gamut A, it ends up on the edge of the triangle
+    let [x, y] = adapt_state(state!(xy: [0.17, 0.7]), &go).xy.unwrap();
+    assert!(x > 0.17 && y < 0.7);
+}
+
+#[test]
+#[cfg(feature = "color")]
+fn test_rgb_to_xy_in_gamut() {
+    use lighthouse::{color::*, lights::*};
+    for gamut in &[Gamut::A, Gamut::B, Gamut::C] {
+        for rgb in &[[255, 0, 0], [0, 255, 0], [0, 0, 255], [255, 136, 0]] {
+            let (xy, _) = rgb_to_xy_in_gamut(*rgb, gamut);
+            assert!(gamut.contains(xy), "{:?} not in {:?}", xy, gamut);
+        }
+    }
+    // colors inside of the gamut are left alone
+    let peach = rgb_to_xy([200, 150, 100]);
+    assert_eq!(rgb_to_xy_in_gamut([200, 150, 100], &Gamut::C).0, peach);
+    assert_eq!(rgb_to_xy_in_gamut([255, 255, 255], &Gamut::C).1, 254);
+    assert_eq!(rgb_to_xy_in_gamut([0, 0, 0], &Gamut::C).1, 0);
+
+    let capabilities: Capabilities =
+        serde_json::from_str(r#"{"control": {"colorgamuttype": "B"}}"#).unwrap();
+    assert_eq!(capabilities.gamut(), Some(Gamut::B));
+    let capabilities: Capabilities = serde_json::from_str(
+        r#"{"control": {"colorgamuttype": "other", "colorgamut": [[0.6, 0.3], [0.2, 0.6], [0.1, 0.1]]}}"#,
+    )
+    .unwrap();
+    assert_eq!(capabilities.gamut().unwrap().green, [0.2, 0.6]);
+}
+
+#[test]
+#[cfg(feature = "color")]
+fn test_reverse_color_conversions() {
+    use lighthouse::{color::*, lights::*};
+    // the primaries of the conversion matrix, so that no color gets clamped
+    let wide = Gamut {
+        red: [0.701, 0.299],
+        green: [0.172, 0.747],
+        blue: [0.135, 0.039],
+    };
+    let close = |a: &[u8], b: &[u8]| {
+        a.iter()
+            .zip(b)
+            .all(|(a, b)| (i16::from(*a) - i16::from(*b)).abs() <= 3)
+    };
+    let steps = (0..=255).step_by(15).map(|c| c as u8);
+    for red in steps.clone() {
+        for green in steps.clone() {
+            for blue in steps.clone() {
+                let rgb = [red, green, blue];
+                assert_eq!(hex_to_rgb(&rgb_to_hex(rgb)).unwrap(), rgb);
+
+                // hsl is computed on linear channels, 8 bits lose the darkest shades
+                let (h, s, l) = rgb_to_hsl(rgb);
+                let back = hsl_to_rgb(h, s, l);
+                assert!(
+                    rgb.iter().any(|c| *c < 60) || close(&back, &rgb),
+                    "hsl {:?} -> {:?}",
+                    rgb,
+                    back
+                );
+
+                let (xy, bri) = rgb_to_xy_in_gamut(rgb, &wide);
+                let back = xy_to_rgb(xy, bri);
+                assert!(bri < 16 || close(&back, &rgb), "xy {:?} -> {:?}", rgb, back);
+            }
+        }
+    }
+
+    let warm = mired_to_rgb(500, 254);
+    let cold = mired_to_rgb(153, 254);
+    assert!(warm[0] == 255 && warm[2] < warm[1]);
+    assert!(cold[2] > warm[2]);
+    assert_eq!(hs_to_rgb(21845, 254, 254), [0, 255, 0]);
+
+    let parse = |json: &str| -> State { serde_json::from_str(json).unwrap() };
+    let white = rgb_to_xy([255, 255, 255]);
+    let on = parse(&format!(
+        r#"{{"on": true, "bri": 254, "xy": [{}, {}], "colormode": "xy"}}"#,
+        white[0], white[1]
+    ));
+    assert!(close(
+        &hex_to_rgb(&state_to_hex(&on)).unwrap(),
+        &[255, 255, 255]
+    ));
+    let dimmable = parse(r#"{"on": true, "bri": 254}"#);
+    assert_eq!(state_to_hex(&dimmable), "#ffffff");
+    let off = parse(r#"{"on": false, "bri": 254, "xy": [0.3127, 0.329], "colormode": "xy"}"#);
+    assert_eq!(state_to_hex(&off), "#000000");
+    let ct = parse(r#"{"on": true, "bri": 254, "ct": 500, "colormode": "ct"}"#);
+    assert_eq!(state_to_hex(&ct), rgb_to_hex(warm));
+}
+
+#[test]
+#[cfg(feature = "color")]
+fn test_color_temperature() {
+    use lighthouse::{color::*, lights::Capabilities};
+    assert_eq!(kelvin_to_mired(6500), 154);
+    assert_eq!(kelvin_to_mired(0), 65535);
+    assert_eq!(mired_to_kelvin(500), 2000);
+    assert_eq!(mired_to_kelvin(0), 1_000_000);
+
+    let unknown = Capabilities::default();
+    assert_eq!(kelvin_to_mired_for(10000, &unknown), 153);
+    assert_eq!(kelvin_to_mired_for(1000, &unknown), 500);
+
+    // the locus and McCamy's approximation agree for usual white temperatures
+    for kelvin in (2000..=10000).step_by(500) {
+        let xy = kelvin_to_xy(kelvin);
+        let back = xy_to_kelvin(xy);
+        assert!(
+            (i64::from(back) - i64::from(kelvin)).abs() < i64::from(kelvin) / 50,
+            "{}K -> {:?} -> {}K",
+            kelvin,
+            xy,
+            back
+        );
+    }
+    assert_eq!(kelvin_to_xy(1000), kelvin_to_xy(1667));
+    let [x, y] = kelvin_to_xy(2700);
+    assert!((x - 0.46).abs() < 0.005 && (y - 0.41).abs() < 0.005);
+}
+
+#[test]
+#[cfg(feature = "color")]
+fn test_color_from_str() {
+    use lighthouse::{color::Color, lights::SendableState};
+    let parse = |s: &str| s.parse::<Color>().unwrap();
+    let orange = Color::Rgb(255, 136, 0);
+    for s in &[
+        "#ff8800",
+        "#FF8800",
+        "#f80",
+        " rgb(255, 136, 0) ",
+        "rgb(255 136 0)",
+        "rgba(255, 136, 0, 0.5)",
+        "rgb(100%, 53.3%, 0%)",
+        "hsl(32, 100%, 50%)",
+        "hsl(32deg 100% 50% / 50%)",
+    ] {
+        assert_eq!(parse(s), orange, "{}", s);
+    }
+    assert_eq!(parse("RebeccaPurple"), Color::Rgb(102, 51, 153));
+    assert_eq!(parse("light goldenrod yellow"), Color::Rgb(250, 250, 210));
+    assert_eq!(parse("hsl(0, 0%, 100%)"), Color::Rgb(255, 255, 255));
+    assert_eq!(parse("hsl(240, 100%, 25%)"), parse("navy"));
+    assert_eq!(parse("2700K"), Color::Temperature(2700));
+    assert_eq!(parse("6500 k"), Color::Temperature(6500));
+    // names ending with a 'k' are not temperatures
+    assert_eq!(parse("black"), Color::Rgb(0, 0, 0));
+    assert_eq!(parse("HotPink"), Color::Rgb(255, 105, 180));
+    for s in &[
+        "",
+        "#ff88",
+        "#ff880",
+        "rgb(1, 2)",
+        "rgb(a, b, c)",
+        "cmyk(0, 0, 0)",
+        "rgb(1, 2, 3",
+        "notacolor",
+        "warmK",
+    ] {
+        assert!(s.parse::<Color>().is_err(), "{}", s);
+    }
+
+    assert_eq!(parse("#f80").to_string(), "#ff8800");
+    assert_eq!(parse("2700K").to_string(), "2700K");
+    assert_eq!(parse("red").to_rgb(), [255, 0, 0]);
+    let warm = parse("2000K").to_rgb();
+    assert!(warm[0] > warm[1] && warm[1] > warm[2]);
+
+    let state = SendableState::from(parse("10000K"));
+    assert_eq!((state.ct, state.xy), (Some(153), None));
+    let state = SendableState::from(parse("red"));
+    assert!(state.xy.is_some() && state.ct.is_none());
+    let state = SendableState::builder()
+        .on()
+        .color(parse("2700K"))
+        .build()
+        .unwrap();
+    assert_eq!(state.ct, Some(370));
+}
+
+#[test]
+#[cfg(feature = "color")]
+fn test_perceptual_interpolation() {
+    use lighthouse::color::*;
+    assert_eq!(lightness_to_bri(100.0), 254);
+    assert_eq!(lightness_to_bri(0.0), 1);
+    assert!((bri_to_lightness(254) - 100.0).abs() < 0.01);
+    for bri in 1..=254 {
+        assert_eq!(lightness_to_bri(bri_to_lightness(bri)), bri);
+    }
+
+    let bri = interpolate_bri(1, 254, 5);
+    assert_eq!((bri.len(), bri[0], bri[4]), (5, 1, 254));
+    assert!(bri.windows(2).all(|w| w[0] < w[1]));
+    // the middle of a perceptual fade is darker than the linear middle
+    assert!(bri[2] < 127);
+    assert_eq!(interpolate_bri(10, 200, 1), vec![10]);
+    assert!(interpolate_bri(10, 200, 0).is_empty());
+
+    let red: Color = "red".parse().unwrap();
+    let blue: Color = "blue".parse().unwrap();
+    let lab = interpolate_lab(red, blue, 3);
+    let lch = interpolate_lch(red, blue, 3);
+    assert_eq!((lab[0], lab[2]), (red, blue));
+    assert_eq!((lch[0], lch[2]), (red, blue));
+    // LCh keeps the middle of the blend saturated where Lab goes muddy
+    let saturation = |color: &Color| {
+        let rgb = color.to_rgb();
+        rgb.iter().max().unwrap() - rgb.iter().min().unwrap()
+    };
+    assert!(saturation(&lch[1]) > saturation(&lab[1]));
+
+    let states = fade((red, 254), (blue, 1), 4);
+    assert_eq!(states.len(), 4);
+    assert_eq!(states[3].bri, Some(1));
+    assert_eq!(states[0].xy, Some(rgb_to_xy([255, 0, 0])));
+    assert!(states.iter().all(|state| state.validate().is_ok()));
+}
+
+/// Serves the answers of `respond` to a method and path on a local port, standing in
+/// for a bridge
+fn mock_bridge<F>(respond: F) -> u16
+where
+    F: Fn(&str, &str) -> String + Send + Sync + 'static,
+{
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+        sync::Arc,
+    };
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let respond = Arc::new(respond);
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let respond = respond.clone();
+            std::thread::spawn(move || {
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                if reader.read_line(&mut request_line).unwrap_or(0) == 0 {
+                    return;
+                }
+                let mut length = 0;
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header.trim().is_empty() {
+                        break;
+                    }
+                    let header = header.to_ascii_lowercase();
+                    if let Some(value) = header.strip_prefix("content-length:") {
+                        length = value.trim().parse().unwrap();
+                    }
+                }
+                reader.read_exact(&mut vec![0; length]).unwrap();
+                let mut parts = request_line.split_whitespace();
+                let (method, path) = (parts.next().unwrap(), parts.next().unwrap());
+                let body = respond(method, path);
+                write!(
+                    &stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                )
+                .unwrap();
+            });
+        }
+    });
+    port
+}
+
+#[test]
+fn test_concurrent_light_cache() {
+    use lighthouse::bridge::BridgeBuilder;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    let lights = format!(
+        r#"{{"1": {}, "2": {}}}"#,
+        include_str!("json_examples/light.json"),
+        include_str!("json_examples/lightstrip.json")
+    );
+    let scans = Arc::new(AtomicUsize::new(0));
+    let counter = scans.clone();
+    let port = mock_bridge(move |method, path| match (method, path) {
+        ("GET", "/api/token/lights") => {
+            counter.fetch_add(1, Ordering::SeqCst);
+            lights.clone()
+        }
+        ("POST", "/api/token/lights") => {
+            String::from(r#"[{"success": {"/lights": "Searching for new devices"}}]"#)
+        }
+        _ => String::from(r#"[{"success": {"/lights/state/on": true}}]"#),
+    });
+
+    let mut runtime = tokio::runtime::Builder::new()
+        .threaded_scheduler()
+        .enable_all()
+        .build()
+        .unwrap();
+    runtime.block_on(async move {
+        let bridge = BridgeBuilder::new()
+            .host("127.0.0.1")
+            .port(port)
+            .token("token")
+            .build_async()
+            .await
+            .unwrap();
+        let bridge = Arc::new(bridge);
+        let state = lighthouse::state!(nonref; on: true);
+
+        // readers and senders race with searches, which drop the cache
+        let tasks: Vec<_> = (0..60)
+            .map(|task| {
+                let bridge = bridge.clone();
+                let state = state.clone();
+                tokio::spawn(async move {
+                    match task % 3 {
+                        0 => {
+                            bridge.search_lights(&[]).await.unwrap();
+                        }
+                        1 => {
+                            let lights = bridge.get_lights().await.unwrap();
+                            assert_eq!(lights.keys().collect::<Vec<_>>(), vec![&1, &2]);
+                            assert_eq!(lights[&1].name, "xxx");
+                        }
+                        _ => assert_eq!(bridge.to_all(&state).await.unwrap().len(), 2),
+                    }
+                })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap();
+        }
+        assert_eq!(bridge.get_lights().await.unwrap().len(), 2);
+    });
+    assert!(scans.load(Ordering::SeqCst) >= 1);
+}
+
+#[test]
+fn test_wait_for_search_timeout() {
+    use lighthouse::{bridge::BridgeBuilder, search::LastScan, Error};
+    use std::time::{Duration, Instant};
+
+    let port = mock_bridge(|_, path| match path {
+        "/api/token/lights/new" => String::from(r#"{"lastscan": "active"}"#),
+        _ => String::from(
+            r#"{"5": {"name": "Hue motion sensor 1"}, "lastscan": "2020-07-03T12:34:56"}"#,
+        ),
+    });
+    let bridge = BridgeBuilder::new()
+        .host("127.0.0.1")
+        .port(port)
+        .token("token")
+        .build()
+        .unwrap();
+
+    let start = Instant::now();
+    match bridge.wait_for_new_lights(Duration::from_millis(200)) {
+        Err(Error::Timeout(_)) => {}
+        other => panic!("Expected a timeout, got {:?}", other),
+    }
+    assert!(start.elapsed() < Duration::from_secs(2));
+
+    let found = bridge
+        .wait_for_new_sensors(Duration::from_millis(200))
+        .unwrap();
+    assert_eq!(
+        found.lastscan,
+        LastScan::Completed(String::from("2020-07-03T12:34:56"))
+    );
+    assert_eq!(found.devices[&5].name, "Hue motion sensor 1");
+}
+
+#[test]
+fn test_update_light_config_invalidates_cache() {
+    use lighthouse::{bridge::BridgeBuilder, lights::*};
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    let scans = Arc::new(AtomicUsize::new(0));
+    let counter = scans.clone();
+    let port = mock_bridge(move |method, path| match (method, path) {
+        ("GET", "/api/token/lights") => {
+            counter.fetch_add(1, Ordering::SeqCst);
+            format!(r#"{{"1": {}}}"#, include_str!("json_examples/light.json"))
+        }
+        _ => String::from(r#"[{"success": {"/lights/1/config/startup/mode": "powerfail"}}]"#),
+    });
+    let bridge = BridgeBuilder::new()
+        .host("127.0.0.1")
+        .port(port)
+        .token("token")
+        .build()
+        .unwrap();
+
+    bridge.get_lights().unwrap();
+    bridge.get_lights().unwrap();
+    assert_eq!(scans.load(Ordering::SeqCst), 1);
+    let update = LightConfigUpdate {
+        startup: Some(StartupBehavior::Powerfail),
+    };
+    assert!(bridge.update_light_config(1, &update).unwrap().is_success());
+    bridge.get_lights().unwrap();
+    assert_eq!(scans.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn test_validate_unauthorized_token() {
+    use lighthouse::{bridge::BridgeBuilder, response::ErrorType, Error};
+
+    let port = mock_bridge(|_, path| match path {
+        "/api/token/config" => String::from(r#"{"name": "Philips hue", "apiversion": "1.39.0"}"#),
+        _ => String::from(r#"{"name": "Philips hue", "whitelist": {}}"#),
+    });
+    let build = |token: &str| {
+        BridgeBuilder::new()
+            .host("127.0.0.1")
+            .port(port)
+            .token(token)
+            .build()
+            .unwrap()
+    };
+    match build("token").validate() {
+        Err(Error::Api(error)) => {
+            assert_eq!(error.kind, ErrorType::UnauthorizedUser);
+            assert_eq!(error.address, "/config");
+        }
+        other => panic!("expected an unauthorized user error, got {:?}", other),
+    }
+    assert!(build("other").validate().is_ok());
+}